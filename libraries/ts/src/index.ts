@@ -4,7 +4,13 @@ import { u64 } from "@solana/spl-token";
 
 export { JetClient } from "./client";
 export { JetMarket, MarketFlags } from "./market";
-export { JetReserve, ReserveConfig } from "./reserve";
+export {
+  JetReserve,
+  ReserveConfig,
+  RateKnot,
+  RateModel,
+  encodeReserveConfig,
+} from "./reserve";
 export { JetUser } from "./user";
 
 export const PLACEHOLDER_ACCOUNT = PublicKey.default;
@@ -3,7 +3,7 @@ import { TOKEN_PROGRAM_ID, u64 } from "@solana/spl-token";
 import * as anchor from "@project-serum/anchor";
 import * as BL from "@solana/buffer-layout";
 
-import { CreateReserveParams, JetReserve } from "./reserve";
+import { CreateReserveParams, JetReserve, encodeReserveConfig } from "./reserve";
 import { JetClient, DEX_ID, DEX_ID_DEVNET } from ".";
 import * as util from "./util";
 
@@ -136,7 +136,7 @@ export class JetMarket implements JetMarketData {
 
     const dexProgram = this.client.devnet ? DEX_ID_DEVNET : DEX_ID;
 
-    await this.client.program.rpc.initReserve(bumpSeeds, params.config, {
+    await this.client.program.rpc.initReserve(bumpSeeds, encodeReserveConfig(params.config), {
       accounts: {
         market: this.address,
         marketAuthority: this.marketAuthority,
@@ -27,9 +27,97 @@ export interface ReserveConfig {
   manageFeeCollectionThreshold: anchor.BN;
   manageFeeRate: number;
   loanOriginationFee: number;
-  liquidationSlippage: number;
   liquidationDexTradeMax: anchor.BN;
   confidenceThreshold: number;
+
+  // Settings below are disabled or use their default when left out
+  flashLoanFee?: number;
+  maxTotalDeposits?: anchor.BN;
+  maxOutstandingDebt?: anchor.BN;
+  liquidationCollateralRatio?: number;
+  collateralFactor?: number;
+  maxOracleAge?: number;
+  oracleMaxDeviation?: number;
+  priceHaltThreshold?: number;
+  priceHaltWindow?: number;
+  rateModel?: RateModel;
+  adaptiveTargetUtilization?: number;
+  adaptiveAdjustmentSpeed?: number;
+  adaptiveCurveSteepness?: number;
+  adaptiveMinRate?: number;
+  adaptiveMaxRate?: number;
+  insuranceFeeShare?: number;
+  keeperBountyShare?: number;
+
+  /**
+   * The points of a piecewise-linear interest rate curve, which replaces the
+   * rate regimes when given.
+   */
+  rateCurve?: RateKnot[];
+}
+
+export interface RateKnot {
+  utilizationRate: number;
+  borrowRate: number;
+}
+
+export enum RateModel {
+  Curve = 0,
+  Adaptive = 1,
+}
+
+export const MAX_RATE_CURVE_KNOTS = 8;
+
+/**
+ * Fill in the defaults for a reserve config, to match the layout expected by the program.
+ * @param config The config to be sent to the program.
+ */
+export function encodeReserveConfig(config: ReserveConfig): any {
+  const rateCurve = config.rateCurve ?? [];
+
+  if (rateCurve.length > MAX_RATE_CURVE_KNOTS) {
+    throw new Error(`rate curve cannot have more than ${MAX_RATE_CURVE_KNOTS} points`);
+  }
+
+  const zero = new anchor.BN(0);
+
+  return {
+    utilizationRate1: config.utilizationRate1,
+    utilizationRate2: config.utilizationRate2,
+    borrowRate0: config.borrowRate0,
+    borrowRate1: config.borrowRate1,
+    borrowRate2: config.borrowRate2,
+    borrowRate3: config.borrowRate3,
+    minCollateralRatio: config.minCollateralRatio,
+    liquidationPremium: config.liquidationPremium,
+    manageFeeCollectionThreshold: config.manageFeeCollectionThreshold,
+    manageFeeRate: config.manageFeeRate,
+    loanOriginationFee: config.loanOriginationFee,
+    flashLoanFee: config.flashLoanFee ?? 0,
+    confidenceThreshold: config.confidenceThreshold,
+    liquidationDexTradeMax: config.liquidationDexTradeMax,
+    maxTotalDeposits: config.maxTotalDeposits ?? zero,
+    maxOutstandingDebt: config.maxOutstandingDebt ?? zero,
+    liquidationCollateralRatio: config.liquidationCollateralRatio ?? 0,
+    collateralFactor: config.collateralFactor ?? 0,
+    maxOracleAge: config.maxOracleAge ?? 0,
+    oracleMaxDeviation: config.oracleMaxDeviation ?? 0,
+    priceHaltThreshold: config.priceHaltThreshold ?? 0,
+    priceHaltWindow: config.priceHaltWindow ?? 0,
+    rateCurveLen: rateCurve.length,
+    rateModel: config.rateModel ?? RateModel.Curve,
+    adaptiveTargetUtilization: config.adaptiveTargetUtilization ?? 0,
+    adaptiveAdjustmentSpeed: config.adaptiveAdjustmentSpeed ?? 0,
+    adaptiveCurveSteepness: config.adaptiveCurveSteepness ?? 0,
+    adaptiveMinRate: config.adaptiveMinRate ?? 0,
+    adaptiveMaxRate: config.adaptiveMaxRate ?? 0,
+    insuranceFeeShare: config.insuranceFeeShare ?? 0,
+    keeperBountyShare: config.keeperBountyShare ?? 0,
+    reserved1: new Array(8).fill(0),
+    rateCurve: [...Array(MAX_RATE_CURVE_KNOTS).keys()].map(
+      (i) => rateCurve[i] ?? { utilizationRate: 0, borrowRate: 0 }
+    ),
+  };
 }
 
 export interface ReserveAccounts {
@@ -209,14 +297,17 @@ export class JetReserve {
   }
 
   async updateReserveConfig(params: UpdateReserveConfigParams): Promise<void> {
-    await this.client.program.rpc.updateReserveConfig(params.config, {
-      accounts: {
-        market: params.market,
-        reserve: params.reserve,
-        owner: params.owner.publicKey,
-      },
-      signers: [params.owner],
-    });
+    await this.client.program.rpc.updateReserveConfig(
+      encodeReserveConfig(params.config),
+      {
+        accounts: {
+          market: params.market,
+          reserve: params.reserve,
+          owner: params.owner.publicKey,
+        },
+        signers: [params.owner],
+      }
+    );
   }
 
   static async load(
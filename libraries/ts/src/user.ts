@@ -69,15 +69,24 @@ export class JetUser implements User {
 
   async liquidateDex(
     loanReserve: JetReserve,
-    collateralReserve: JetReserve
+    collateralReserve: JetReserve,
+    keeperAccount: PublicKey,
+    quoteReserve?: JetReserve
   ): Promise<string> {
-    const tx = await this.makeLiquidateDexTx(loanReserve, collateralReserve);
+    const tx = await this.makeLiquidateDexTx(
+      loanReserve,
+      collateralReserve,
+      keeperAccount,
+      quoteReserve
+    );
     return await this.client.program.provider.send(tx);
   }
 
   async makeLiquidateDexTx(
     loanReserve: JetReserve,
-    collateralReserve: JetReserve
+    collateralReserve: JetReserve,
+    keeperAccount: PublicKey,
+    quoteReserve?: JetReserve
   ): Promise<Transaction> {
     const loanDexAccounts = await loanReserve.loadDexMarketAccounts();
     const collateralDexAccounts =
@@ -92,6 +101,18 @@ export class JetUser implements User {
     tx.add(loanReserve.makeRefreshIx());
     tx.add(collateralReserve.makeRefreshIx());
 
+    // When neither token is the quote token, the trade is routed through it,
+    // which needs the quote token's reserve to price it.
+    const remainingAccounts = [];
+
+    if (quoteReserve) {
+      tx.add(quoteReserve.makeRefreshIx());
+      remainingAccounts.push(
+        { pubkey: quoteReserve.address, isSigner: false, isWritable: true },
+        { pubkey: quoteReserve.data.vault, isSigner: false, isWritable: true }
+      );
+    }
+
     tx.add(
       this.client.program.instruction.liquidateDex({
         accounts: {
@@ -117,8 +138,11 @@ export class JetUser implements User {
           dexProgram: this.client.devnet ? DEX_ID_DEVNET : DEX_ID,
 
           tokenProgram: TOKEN_PROGRAM_ID,
-          rent: SYSVAR_RENT_PUBKEY
+          rent: SYSVAR_RENT_PUBKEY,
+
+          keeperAccount,
         },
+        remainingAccounts,
       })
     );
 
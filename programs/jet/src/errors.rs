@@ -99,6 +99,69 @@ pub enum ErrorCode {
 
     #[msg("the collateral/loan account is not empty")]
     AccountNotEmptyError,
+
+    #[msg("the flash loan was not repaid with the required fees")]
+    FlashLoanNotRepaid,
+
+    #[msg("the deposit would exceed the maximum total deposits allowed by the reserve")]
+    DepositCapExceeded,
+
+    #[msg("the borrow would exceed the maximum outstanding debt allowed by the reserve")]
+    BorrowCapExceeded,
+
+    #[msg("the reserve has currently halted this kind of operation")]
+    ReserveHalted,
+
+    #[msg("the reserve has no pending config change")]
+    NoPendingReserveConfig,
+
+    #[msg("the pending reserve config change cannot be applied yet")]
+    PendingReserveConfigNotReady,
+
+    #[msg("the signer is not the pending owner of the market")]
+    NotPendingMarketOwner,
+
+    #[msg("the reserve is deprecated and not accepting new deposits or borrows")]
+    ReserveDeprecated,
+
+    #[msg("the reserve must be deprecated and empty before it can be removed")]
+    ReserveNotRemovable,
+
+    #[msg("the oracle price is stale or not currently trading")]
+    StaleOracle,
+
+    #[msg("the primary and secondary oracle prices disagree")]
+    OracleDeviation,
+
+    #[msg("the account is not owned by the market's fee treasury")]
+    InvalidFeeTreasury,
+
+    #[msg("the obligation still has collateral to be liquidated")]
+    ObligationHasCollateral,
+
+    #[msg("the reserve has an outstanding flash loan")]
+    ReserveLocked,
+
+    #[msg("the reserve is not registered in the market")]
+    ReserveNotRegistered,
+
+    #[msg("the reserve already has a pending config change")]
+    ReserveConfigPending,
+
+    #[msg("the market has no pending asset category change")]
+    NoPendingAssetCategory,
+
+    #[msg("the pending asset category change cannot be applied yet")]
+    PendingAssetCategoryNotReady,
+
+    #[msg("the reserve must be refreshed first")]
+    ReserveStale,
+
+    #[msg("the reserve has no pending category change")]
+    NoPendingReserveCategory,
+
+    #[msg("the pending reserve category change cannot be applied yet")]
+    PendingReserveCategoryNotReady,
 }
 
 impl From<jet_math::Error> for ErrorCode {
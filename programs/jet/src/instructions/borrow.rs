@@ -111,10 +111,14 @@ pub fn handler(ctx: Context<Borrow>, _bump: u8, amount: Amount) -> ProgramResult
     let mut reserve = ctx.accounts.reserve.load_mut()?;
     let loan_account = &ctx.accounts.loan_account.key();
 
-    market.verify_ability_borrow()?;
+    market.verify_ability_borrow(&reserve)?;
+    reserve.verify_not_deprecated()?;
 
     let market_reserves = market.reserves();
     let clock = Clock::get().unwrap();
+
+    market_reserves.verify_registered(reserve.index, &ctx.accounts.reserve.key())?;
+
     let reserve_info = market_reserves.get_cached(reserve.index, clock.slot);
 
     let requested_tokens = amount.as_tokens(reserve_info, Rounding::Down);
@@ -123,6 +127,8 @@ pub fn handler(ctx: Context<Borrow>, _bump: u8, amount: Amount) -> ProgramResult
         .checked_add(fees)
         .expect("Requested a debt that would exceed the maximum potential supply for a token.");
 
+    reserve.verify_borrow_cap(clock.slot, total_token_debt)?;
+
     // Calculate the number of notes to create to match the value being
     // borrowed plus the fees, then mint the notes as a way of tracking
     // this borrower's debt.
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Copyright (C) 2021 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CancelReserveConfig<'info> {
+    #[account(has_one = owner)]
+    pub market: Loader<'info, Market>,
+
+    #[account(mut, has_one = market)]
+    pub reserve: Loader<'info, Reserve>,
+
+    #[account(signer)]
+    pub owner: AccountInfo<'info>,
+}
+
+/// Discard a queued config change for a reserve
+pub fn handler(ctx: Context<CancelReserveConfig>) -> ProgramResult {
+    let mut reserve = ctx.accounts.reserve.load_mut()?;
+    reserve.verify_unlocked()?;
+
+    reserve.cancel_pending_config();
+
+    Ok(())
+}
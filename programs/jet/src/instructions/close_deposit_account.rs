@@ -112,9 +112,13 @@ pub fn handler(ctx: Context<CloseDepositAccount>, _bump: u8) -> ProgramResult {
     let notes_remaining = token::accessor::amount(&ctx.accounts.deposit_account)?;
 
     if notes_remaining > 0 {
-        market.verify_ability_deposit_withdraw()?;
-
         let mut reserve = ctx.accounts.reserve.load_mut()?;
+
+        market.verify_ability_deposit_withdraw(&reserve)?;
+        market
+            .reserves()
+            .verify_registered(reserve.index, &ctx.accounts.reserve.key())?;
+
         let clock = Clock::get()?;
 
         let reserve_info = market.reserves().get_cached(reserve.index, clock.slot);
@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Copyright (C) 2021 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use anchor_lang::prelude::*;
+use anchor_lang::Key;
+use anchor_spl::token::{self, TokenAccount, Transfer};
+
+use crate::errors::ErrorCode;
+use crate::state::*;
+
+#[event]
+pub struct FeesCollected {
+    reserve: Pubkey,
+    treasury_account: Pubkey,
+    fee_notes: u64,
+}
+
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    /// The market the fees were collected in
+    #[account(has_one = owner,
+              has_one = market_authority)]
+    pub market: Loader<'info, Market>,
+
+    /// The market's authority account, which owns the fee vault
+    pub market_authority: AccountInfo<'info>,
+
+    /// The reserve the fees were collected by
+    #[account(has_one = market,
+              has_one = fee_note_vault)]
+    pub reserve: Loader<'info, Reserve>,
+
+    /// The reserve's vault holding the collected fee notes
+    #[account(mut)]
+    pub fee_note_vault: Account<'info, TokenAccount>,
+
+    /// The treasury's account to receive the fee notes
+    #[account(mut)]
+    pub treasury_account: Account<'info, TokenAccount>,
+
+    /// The owner of the market
+    #[account(signer)]
+    pub owner: AccountInfo<'info>,
+
+    #[account(address = token::ID)]
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> CollectFees<'info> {
+    fn transfer_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.clone(),
+            Transfer {
+                from: self.fee_note_vault.to_account_info(),
+                to: self.treasury_account.to_account_info(),
+                authority: self.market_authority.clone(),
+            },
+        )
+    }
+}
+
+/// Move the fee notes collected by a reserve to the market's treasury
+pub fn handler(ctx: Context<CollectFees>) -> ProgramResult {
+    let market = ctx.accounts.market.load()?;
+    let reserve = ctx.accounts.reserve.load()?;
+
+    reserve.verify_unlocked()?;
+
+    if market.fee_treasury == Pubkey::default()
+        || ctx.accounts.treasury_account.owner != market.fee_treasury
+    {
+        return Err(ErrorCode::InvalidFeeTreasury.into());
+    }
+
+    let fee_notes = ctx.accounts.fee_note_vault.amount;
+
+    token::transfer(
+        ctx.accounts
+            .transfer_context()
+            .with_signer(&[&market.authority_seeds()]),
+        fee_notes,
+    )?;
+
+    emit!(FeesCollected {
+        reserve: ctx.accounts.reserve.key(),
+        treasury_account: ctx.accounts.treasury_account.key(),
+        fee_notes
+    });
+
+    msg!("collected {} fee notes", fee_notes);
+    Ok(())
+}
@@ -105,9 +105,15 @@ pub fn handler(
     let market = &ctx.accounts.market.load()?;
     let reserve = ctx.accounts.reserve.load()?;
     let clock = Clock::get()?;
+
+    market
+        .reserves()
+        .verify_registered(reserve.index, &ctx.accounts.reserve.key())?;
+
     let reserve_info = market.reserves().get_cached(reserve.index, clock.slot);
 
-    market.verify_ability_deposit_withdraw()?;
+    market.verify_ability_deposit_withdraw(&reserve)?;
+    reserve.verify_not_deprecated()?;
 
     let note_amount = amount.as_deposit_notes(reserve_info, Rounding::Down)?;
 
@@ -90,15 +90,22 @@ pub fn handler(ctx: Context<DepositTokens>, amount: Amount) -> ProgramResult {
     let market = ctx.accounts.market.load()?;
     let mut reserve = ctx.accounts.reserve.load_mut()?;
     let clock = Clock::get()?;
+
+    market
+        .reserves()
+        .verify_registered(reserve.index, &ctx.accounts.reserve.key())?;
+
     let reserve_info = market.reserves().get_cached(reserve.index, clock.slot);
 
-    market.verify_ability_deposit_withdraw()?;
+    market.verify_ability_deposit_withdraw(&reserve)?;
+    reserve.verify_not_deprecated()?;
 
     // Calculate the number of new notes that need to be minted to represent
     // the current value being deposited
     let token_amount = amount.as_tokens(reserve_info, Rounding::Up);
     let note_amount = amount.as_deposit_notes(reserve_info, Rounding::Down)?;
 
+    reserve.verify_deposit_cap(token_amount)?;
     reserve.deposit(token_amount, note_amount);
 
     // Now that we have the note value, we can transfer this deposit
@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Copyright (C) 2021 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::Key;
+use anchor_spl::token::{self, Transfer};
+
+use crate::state::*;
+
+#[event]
+pub struct FlashLoanEvent {
+    reserve: Pubkey,
+    receiver_program: Pubkey,
+    amount: u64,
+    fees: u64,
+}
+
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    /// The relevant market this flash loan is for
+    #[account(has_one = market_authority)]
+    pub market: Loader<'info, Market>,
+
+    /// The market's authority account
+    pub market_authority: AccountInfo<'info>,
+
+    /// The reserve being borrowed from
+    #[account(mut,
+              has_one = market,
+              has_one = vault)]
+    pub reserve: Loader<'info, Reserve>,
+
+    /// The reserve's vault where the borrowed tokens will be transferred from,
+    /// and where they must be returned to before the instruction completes.
+    #[account(mut)]
+    pub vault: AccountInfo<'info>,
+
+    /// The token account that the borrowed funds will be transferred to
+    #[account(mut, constraint = receiver_account.key() != vault.key())]
+    pub receiver_account: AccountInfo<'info>,
+
+    /// The program invoked with the borrowed funds, which is responsible
+    /// for returning them to the vault along with the fees.
+    #[account(executable, constraint = receiver_program.key() != crate::ID)]
+    pub receiver_program: AccountInfo<'info>,
+
+    #[account(address = token::ID)]
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> FlashLoan<'info> {
+    fn transfer_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.clone(),
+            Transfer {
+                from: self.vault.to_account_info(),
+                to: self.receiver_account.to_account_info(),
+                authority: self.market_authority.clone(),
+            },
+        )
+    }
+}
+
+/// Lend tokens from a reserve, which must be repaid within the same instruction
+///
+/// Any remaining accounts are passed along to the receiving program, which is
+/// invoked with the given instruction data after the tokens are transferred.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, FlashLoan<'info>>,
+    amount: u64,
+    data: Vec<u8>,
+) -> ProgramResult {
+    let clock = Clock::get()?;
+    let vault_tokens_before = token::accessor::amount(&ctx.accounts.vault)?;
+    let fees = {
+        let market = ctx.accounts.market.load()?;
+        let mut reserve = ctx.accounts.reserve.load_mut()?;
+
+        market
+            .reserves()
+            .verify_registered(reserve.index, &ctx.accounts.reserve.key())?;
+        market.verify_ability_borrow(&reserve)?;
+        reserve.verify_not_deprecated()?;
+
+        // Keep the reserve from being used by anything the receiver invokes
+        // until the loan has been repaid.
+        reserve.begin_flash_loan(clock.slot)?;
+
+        token::transfer(
+            ctx.accounts
+                .transfer_context()
+                .with_signer(&[&market.authority_seeds()]),
+            amount,
+        )?;
+
+        reserve.flash_loan_fee(amount)
+    };
+
+    // Hand control over to the receiving program, which is expected to use
+    // the funds and transfer them back into the vault before returning.
+    let receiver_accounts = ctx.remaining_accounts;
+    let instruction = Instruction {
+        program_id: ctx.accounts.receiver_program.key(),
+        accounts: receiver_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data,
+    };
+
+    let mut invoke_accounts = receiver_accounts.to_vec();
+    invoke_accounts.push(ctx.accounts.receiver_program.clone());
+
+    invoke(&instruction, &invoke_accounts)?;
+
+    // Verify the loan was repaid, with fees, and release the reserve
+    let vault_tokens_after = token::accessor::amount(&ctx.accounts.vault)?;
+    let mut reserve = ctx.accounts.reserve.load_mut()?;
+
+    reserve.end_flash_loan(clock.slot, vault_tokens_before, vault_tokens_after, fees)?;
+
+    emit!(FlashLoanEvent {
+        reserve: ctx.accounts.reserve.key(),
+        receiver_program: ctx.accounts.receiver_program.key(),
+        amount,
+        fees
+    });
+
+    Ok(())
+}
@@ -60,8 +60,16 @@ pub fn handler(ctx: Context<InitializeCollateralAccount>, _bump: u8) -> ProgramR
 
     let mut obligation = ctx.accounts.obligation.load_mut()?;
     let reserve = ctx.accounts.reserve.load()?;
+    reserve.verify_unlocked()?;
+
     let account = ctx.accounts.collateral_account.key();
 
+    ctx.accounts
+        .market
+        .load()?
+        .reserves()
+        .verify_registered(reserve.index, &ctx.accounts.reserve.key())?;
+
     obligation.register_collateral(&account, reserve.index)?;
 
     msg!("initialized collateral account");
@@ -62,7 +62,9 @@ pub struct InitializeDepositAccount<'info> {
 }
 
 /// Initialize an account that can be used to store deposit notes
-pub fn handler(_ctx: Context<InitializeDepositAccount>, _bump: u8) -> ProgramResult {
+pub fn handler(ctx: Context<InitializeDepositAccount>, _bump: u8) -> ProgramResult {
+    ctx.accounts.reserve.load()?.verify_unlocked()?;
+
     // Do nothing, the deposit account should be initialized
     // automatically by anchor during setup for this handler.
 
@@ -77,8 +77,16 @@ pub fn handler(ctx: Context<InitializeLoanAccount>, _bump: u8) -> ProgramResult
 
     let mut obligation = ctx.accounts.obligation.load_mut()?;
     let reserve = &ctx.accounts.reserve.load()?;
+    reserve.verify_unlocked()?;
+
     let account = ctx.accounts.loan_account.key();
 
+    ctx.accounts
+        .market
+        .load()?
+        .reserves()
+        .verify_registered(reserve.index, &ctx.accounts.reserve.key())?;
+
     obligation.register_loan(&account, reserve.index)?;
 
     msg!("initialized loan account");
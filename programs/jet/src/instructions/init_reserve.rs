@@ -232,6 +232,8 @@ impl<'info> InitializeReserve<'info> {
         let oracle_product = &self.oracle_product;
         let token_mint = &self.token_mint;
 
+        config.validate()?;
+
         reserve.version = 0;
         reserve.config = config;
         reserve.market = self.market.key();
@@ -133,16 +133,25 @@ fn transfer_collateral(
     let market = accounts.market.load()?;
     let reserve = accounts.reserve.load()?;
     let collateral_reserve = accounts.collateral_reserve.load()?;
+
+    reserve.verify_unlocked()?;
+    collateral_reserve.verify_unlocked()?;
+
     let mut obligation = accounts.obligation.load_mut()?;
     let clock = Clock::get().unwrap();
 
     let market_reserves = market.reserves();
+
+    market_reserves.verify_registered(reserve.index, &accounts.reserve.key())?;
+    market_reserves
+        .verify_registered(collateral_reserve.index, &accounts.collateral_reserve.key())?;
+
     let reserve_info = market_reserves.get_cached(reserve.index, clock.slot);
 
     obligation.cache_calculations(market.reserves(), clock.slot);
 
     // First check that the obligation is unhealthy
-    if obligation.is_healthy(market_reserves, clock.slot) {
+    if !obligation.is_liquidatable(market_reserves, clock.slot) {
         return Err(ErrorCode::ObligationHealthy.into());
     }
 
@@ -63,6 +63,21 @@ pub struct DexMarketAccounts<'info> {
     vault_signer: AccountInfo<'info>,
 }
 
+/// Accounts used to route a trade through the quote token, when neither the
+/// collateral nor the loan is the quote token
+#[derive(Accounts)]
+pub struct QuoteRouteAccounts<'info> {
+    /// The reserve for the market's quote token, which prices the quote token
+    #[account(mut,
+              constraint = quote_reserve.load().unwrap().vault == quote_reserve_vault.key())]
+    pub quote_reserve: Loader<'info, Reserve>,
+
+    /// The quote reserve's vault, which receives any quote tokens left over
+    /// from the trade
+    #[account(mut)]
+    pub quote_reserve_vault: AccountInfo<'info>,
+}
+
 /// Client for interacting with the DEX program
 struct DexClient<'a, 'info> {
     market: &'a Market,
@@ -245,6 +260,12 @@ pub struct LiquidateDex<'info> {
     #[account(address = token::ID)]
     pub token_program: AccountInfo<'info>,
     pub rent: AccountInfo<'info>,
+
+    /// The token account to receive the bounty for performing the liquidation
+    #[account(mut,
+              constraint = keeper_account.key() != loan_reserve_vault.key(),
+              constraint = token::accessor::mint(&keeper_account).unwrap() == loan_reserve.load().unwrap().token_mint)]
+    pub keeper_account: AccountInfo<'info>,
 }
 
 impl<'info> LiquidateDex<'info> {
@@ -270,6 +291,17 @@ impl<'info> LiquidateDex<'info> {
         )
     }
 
+    fn keeper_bounty_transfer_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.clone(),
+            Transfer {
+                from: self.loan_reserve_vault.clone(),
+                to: self.keeper_account.clone(),
+                authority: self.market_authority.clone(),
+            },
+        )
+    }
+
     fn _transfer_swapped_token_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
         CpiContext::new(
             self.token_program.clone(),
@@ -303,7 +335,7 @@ impl<'info> LiquidateDex<'info> {
 
         obligation.cache_calculations(market.reserves(), clock.slot);
 
-        if obligation.is_healthy(market.reserves(), clock.slot) {
+        if !obligation.is_liquidatable(market.reserves(), clock.slot) {
             msg!("cannot liquidate a healthy position");
             return Err(ErrorCode::ObligationHealthy.into());
         }
@@ -316,6 +348,10 @@ impl<'info> LiquidateDex<'info> {
 enum SwapKind {
     Buy,
     Sell,
+
+    /// Sell the collateral for quote tokens, and then use those to buy the
+    /// loaned tokens, for pairs where neither side is the quote token.
+    SellThenBuy,
 }
 
 #[derive(Debug)]
@@ -342,11 +378,19 @@ struct SwapPlan {
     /// The worst price to accept for the trade
     limit_price: Number,
 
+    /// The worst price to accept for buying the loaned tokens with quote
+    /// tokens, when the trade is routed through the quote token.
+    quote_limit_price: Number,
+
     /// The kind of trade that should be executed
     kind: SwapKind,
 
     /// The acceptable slippage for the trade
     slippage: Number,
+
+    /// Whether the loan is worth more than all the collateral, in which case
+    /// all the proceeds from the collateral go to repaying the loan.
+    underwater: bool,
 }
 
 struct SwapCalculator<'a, 'info> {
@@ -358,6 +402,8 @@ struct SwapCalculator<'a, 'info> {
     obligation: &'a Obligation,
     collateral_reserve_info: &'a CachedReserveInfo,
     loan_reserve_info: &'a CachedReserveInfo,
+    quote_price: Option<Number>,
+    in_category: bool,
 }
 
 impl<'a, 'info> SwapCalculator<'a, 'info> {
@@ -368,12 +414,14 @@ impl<'a, 'info> SwapCalculator<'a, 'info> {
         loan_account: &'a AccountInfo<'info>,
         collateral_account: &'a AccountInfo<'info>,
         obligation: &'a Obligation,
+        quote_price: Option<Number>,
     ) -> SwapCalculator<'a, 'info> {
         let clock = Clock::get().unwrap();
         let collateral_reserve_info = market
             .reserves()
             .get_cached(collateral_reserve.index, clock.slot);
         let loan_reserve_info = market.reserves().get_cached(loan_reserve.index, clock.slot);
+        let in_category = obligation.in_category(market.reserves(), clock.slot);
 
         SwapCalculator {
             market,
@@ -384,11 +432,21 @@ impl<'a, 'info> SwapCalculator<'a, 'info> {
             obligation,
             collateral_reserve_info,
             loan_reserve_info,
+            quote_price,
+            in_category,
         }
     }
 
+    /// The bonus for liquidating the collateral, as a fraction
+    fn liquidation_fee(&self) -> Number {
+        Number::from_bps(
+            self.collateral_reserve_info
+                .effective_liquidation_bonus(self.in_category),
+        )
+    }
+
     fn max_collateral_tradable(&self, sellable_value: Number) -> Result<Number, ProgramError> {
-        let liquidation_fee = Number::from_bps(self.collateral_reserve.config.liquidation_premium);
+        let liquidation_fee = self.liquidation_fee();
 
         // calculate max number of tokens that can be sold from this account
         let max_collateral_tokens = sellable_value / self.collateral_reserve_info.price;
@@ -439,47 +497,93 @@ impl<'a, 'info> SwapCalculator<'a, 'info> {
     /// Calculate the plan for swapping the collateral for debt
     fn plan(&self) -> Result<SwapPlan, ProgramError> {
         let clock = Clock::get()?;
-        let min_c_ratio = Number::from_bps(self.loan_reserve.config.min_collateral_ratio);
-        let liquidation_fee = Number::from_bps(self.collateral_reserve.config.liquidation_premium);
+        let min_c_ratio = self
+            .loan_reserve_info
+            .effective_min_collateral_ratio(self.in_category);
+        let liquidation_c_ratio = self
+            .loan_reserve_info
+            .effective_liquidation_collateral_ratio(self.in_category);
+        let liquidation_fee = self.liquidation_fee();
         let slippage = liquidation_fee / (Number::ONE + liquidation_fee);
 
         let collateral_value = self
             .obligation
             .collateral_value(self.market.reserves(), clock.slot);
+        let collateral_weighted = self
+            .obligation
+            .weighted_collateral_value(self.market.reserves(), clock.slot);
         let loan_value = self
             .obligation
             .loan_value(self.market.reserves(), clock.slot);
 
-        let loan_to_value = loan_value / collateral_value;
-        let c_ratio_ltv = min_c_ratio * loan_to_value;
-
-        if c_ratio_ltv <= Number::ONE {
+        if liquidation_c_ratio * loan_value <= collateral_weighted {
             // This means the loan is over-collateralized, so we shouldn't allow
             // any liquidation for it.
-            msg!("c_ratio_ltv < 1 implies this cannot be liquidated");
+            msg!("collateral value is above the liquidation ratio, so this cannot be liquidated");
             return Err(ErrorCode::ObligationHealthy.into());
-        } else if c_ratio_ltv > min_c_ratio {
-            // This means the loan is underwater, so for now we just disallow
-            // liquidations on underwater loans using the DEX.
-            return Err(ErrorCode::Disallowed.into());
         }
 
-        let limit_fraction = (c_ratio_ltv - Number::ONE)
-            / (min_c_ratio / (Number::ONE + liquidation_fee) - Number::ONE);
-
-        let collateral_sellable_value = limit_fraction * collateral_value;
-        let loan_repay_value = collateral_sellable_value / (Number::ONE + liquidation_fee);
-        let normal_limit_price = (Number::ONE - slippage)
-            * (self.collateral_reserve_info.price / self.loan_reserve_info.price);
-
-        let (kind, limit_price) = if self.loan_reserve.token_mint == self.market.quote_token_mint {
-            (SwapKind::Sell, normal_limit_price)
-        } else if self.collateral_reserve.token_mint == self.market.quote_token_mint {
-            (SwapKind::Buy, Number::ONE / normal_limit_price)
-        } else {
-            msg!("cannot liquidate these pairs");
-            return Err(ErrorCode::Disallowed.into());
+        // When the loan is underwater, there's no way to bring it back to a healthy
+        // position, so all the collateral is sold to repay as much as possible.
+        let underwater = loan_value > collateral_value;
+
+        let (collateral_sellable_value, loan_repay_value) = match underwater {
+            true => (collateral_value, collateral_value),
+            false => {
+                // When no sale can restore the minimum ratio, as much collateral
+                // is sold as the other limits on the trade allow.
+                let collateral_sellable_value = restoring_sell_value(
+                    min_c_ratio,
+                    loan_value,
+                    collateral_weighted,
+                    liquidation_fee,
+                    self.collateral_reserve_info.collateral_factor,
+                )
+                .unwrap_or(collateral_value);
+
+                (
+                    collateral_sellable_value,
+                    collateral_sellable_value / (Number::ONE + liquidation_fee),
+                )
+            }
         };
+        // The limits are set with the side-appropriate prices, valuing the collateral
+        // being sold at its conservative collateral price and the loan tokens being
+        // bought at their conservative debt price, like the obligation does.
+        let normal_limit_price = (Number::ONE - slippage)
+            * (self.collateral_reserve_info.collateral_price / self.loan_reserve_info.debt_price);
+
+        // Direct trades only depend on the ratio between the reserve prices, while
+        // trades routed through the quote token also need to know its price.
+        let (kind, limit_price, quote_limit_price) =
+            if self.loan_reserve.token_mint == self.market.quote_token_mint {
+                (SwapKind::Sell, normal_limit_price, Number::ZERO)
+            } else if self.collateral_reserve.token_mint == self.market.quote_token_mint {
+                (
+                    SwapKind::Buy,
+                    Number::ONE / normal_limit_price,
+                    Number::ZERO,
+                )
+            } else {
+                let quote_price = match self.quote_price {
+                    Some(price) => price,
+                    None => {
+                        msg!("the quote token reserve must be provided to liquidate these pairs");
+                        return Err(ErrorCode::Disallowed.into());
+                    }
+                };
+
+                // Each trade may only slip by half the allowed amount, so that
+                // together they stay within it.
+                let leg_slippage = slippage / 2u64;
+
+                (
+                    SwapKind::SellThenBuy,
+                    (Number::ONE - leg_slippage) * self.collateral_reserve_info.collateral_price
+                        / quote_price,
+                    self.loan_reserve_info.debt_price / quote_price / (Number::ONE - leg_slippage),
+                )
+            };
 
         let collateral_tokens_tradable = self.max_collateral_tradable(collateral_sellable_value)?;
 
@@ -488,19 +592,40 @@ impl<'a, 'info> SwapCalculator<'a, 'info> {
             collateral_tokens_tradable,
             loan_repay_value,
             limit_price,
+            quote_limit_price,
             kind,
             slippage,
+            underwater,
         })
     }
 }
 
 /// Calculate the estimates for swap values
-fn calculate_collateral_swap_plan(internal: &LiquidateDex) -> Result<SwapPlan, ProgramError> {
+fn calculate_collateral_swap_plan(
+    internal: &LiquidateDex,
+    quote_route: Option<&QuoteRouteAccounts>,
+) -> Result<SwapPlan, ProgramError> {
     let loan_reserve = internal.loan_reserve.load()?;
     let collateral_reserve = internal.collateral_reserve.load()?;
+
+    loan_reserve.verify_unlocked()?;
+    collateral_reserve.verify_unlocked()?;
+
     let obligation = internal.obligation.load()?;
     let market = internal.market.load()?;
 
+    market
+        .reserves()
+        .verify_registered(loan_reserve.index, &internal.loan_reserve.key())?;
+    market
+        .reserves()
+        .verify_registered(collateral_reserve.index, &internal.collateral_reserve.key())?;
+
+    let quote_price = match quote_route {
+        Some(route) => Some(quote_token_price(internal, &market, route)?),
+        None => None,
+    };
+
     let calculator = SwapCalculator::new(
         &market,
         &loan_reserve,
@@ -508,11 +633,39 @@ fn calculate_collateral_swap_plan(internal: &LiquidateDex) -> Result<SwapPlan, P
         &internal.loan_account,
         &internal.collateral_account,
         &obligation,
+        quote_price,
     );
 
     calculator.plan()
 }
 
+/// Get the price of the market's quote token from its reserve
+fn quote_token_price(
+    internal: &LiquidateDex,
+    market: &Market,
+    route: &QuoteRouteAccounts,
+) -> Result<Number, ProgramError> {
+    let clock = Clock::get()?;
+    let quote_reserve = route.quote_reserve.load()?;
+
+    if quote_reserve.market != internal.market.key()
+        || quote_reserve.token_mint != market.quote_token_mint
+    {
+        msg!("the quote reserve provided is not for the market's quote token");
+        return Err(ErrorCode::Disallowed.into());
+    }
+
+    quote_reserve.verify_unlocked()?;
+    market
+        .reserves()
+        .verify_registered(quote_reserve.index, &route.quote_reserve.key())?;
+
+    Ok(market
+        .reserves()
+        .get_cached(quote_reserve.index, clock.slot)
+        .price)
+}
+
 /// Execute the calculated plan to swap the collateral.
 ///
 /// Returns the number of collateral tokens swapped.
@@ -520,24 +673,26 @@ fn execute_plan<'info>(
     internal: &LiquidateDex<'info>,
     source_dex_market: &DexMarketAccounts<'info>,
     target_dex_market: &DexMarketAccounts<'info>,
+    quote_route: Option<&QuoteRouteAccounts<'info>>,
     plan: &SwapPlan,
 ) -> Result<(), ProgramError> {
     let market = internal.market.load()?;
     let collateral_reserve = internal.collateral_reserve.load()?;
     let loan_reserve = internal.loan_reserve.load()?;
 
-    let get_dex_client = |dex_market, coin_wallet, pc_wallet| DexClient {
-        market: &market,
-        market_authority: &internal.market_authority,
-        dex_program: &internal.dex_program,
-        dex_market,
-        order_payer_token_account: &internal.collateral_reserve_vault,
-        token_program: &internal.token_program,
-        rent: &internal.rent,
-
-        coin_wallet,
-        pc_wallet,
-    };
+    let get_dex_client =
+        |dex_market, order_payer_token_account, coin_wallet, pc_wallet| DexClient {
+            market: &market,
+            market_authority: &internal.market_authority,
+            dex_program: &internal.dex_program,
+            dex_market,
+            order_payer_token_account,
+            token_program: &internal.token_program,
+            rent: &internal.rent,
+
+            coin_wallet,
+            pc_wallet,
+        };
 
     match plan.kind {
         SwapKind::Sell => {
@@ -545,6 +700,7 @@ fn execute_plan<'info>(
             let dex_client = get_dex_client(
                 source_dex_market,
                 &internal.collateral_reserve_vault,
+                &internal.collateral_reserve_vault,
                 &internal.loan_reserve_vault,
             );
             let limit_price = dex_client.price_lots(
@@ -565,6 +721,7 @@ fn execute_plan<'info>(
             // Use the collateral to buy the debt asset on the DEX
             let dex_client = get_dex_client(
                 target_dex_market,
+                &internal.collateral_reserve_vault,
                 &internal.loan_reserve_vault,
                 &internal.collateral_reserve_vault,
             );
@@ -581,12 +738,95 @@ fn execute_plan<'info>(
             )?;
             dex_client.settle()?;
         }
+
+        SwapKind::SellThenBuy => {
+            let quote_route = match quote_route {
+                Some(route) => route,
+                None => {
+                    msg!("the quote token reserve must be provided to liquidate these pairs");
+                    return Err(ErrorCode::Disallowed.into());
+                }
+            };
+            let quote_exponent = quote_route.quote_reserve.load()?.exponent;
+
+            // Sell the collateral for quote tokens, held in the swap account
+            let quote_tokens_before = token::accessor::amount(&internal.dex_swap_tokens)?;
+            let dex_client = get_dex_client(
+                source_dex_market,
+                &internal.collateral_reserve_vault,
+                &internal.collateral_reserve_vault,
+                &internal.dex_swap_tokens,
+            );
+            let limit_price = dex_client.price_lots(
+                plan.limit_price,
+                quote_exponent,
+                collateral_reserve.exponent,
+            )?;
+
+            dex_client.sell(
+                limit_price,
+                plan.collateral_tokens_tradable
+                    .as_u64_rounded(collateral_reserve.exponent),
+            )?;
+            dex_client.settle()?;
+
+            let quote_tokens = token::accessor::amount(&internal.dex_swap_tokens)?
+                .saturating_sub(quote_tokens_before);
+
+            // Use the quote tokens to buy the debt asset
+            let dex_client = get_dex_client(
+                target_dex_market,
+                &internal.dex_swap_tokens,
+                &internal.loan_reserve_vault,
+                &internal.dex_swap_tokens,
+            );
+            let limit_price = dex_client.price_lots(
+                plan.quote_limit_price,
+                quote_exponent,
+                loan_reserve.exponent,
+            )?;
+
+            dex_client.buy(limit_price, quote_tokens)?;
+            dex_client.settle()?;
+
+            // Anything not spent when the order is only partly filled is given
+            // to the quote reserve, instead of being left in the swap account.
+            let quote_tokens_left = token::accessor::amount(&internal.dex_swap_tokens)?
+                .saturating_sub(quote_tokens_before);
+
+            if quote_tokens_left > 0 {
+                token::transfer(
+                    CpiContext::new(
+                        internal.token_program.clone(),
+                        Transfer {
+                            from: internal.dex_swap_tokens.clone(),
+                            to: quote_route.quote_reserve_vault.clone(),
+                            authority: internal.market_authority.clone(),
+                        },
+                    )
+                    .with_signer(&[&market.authority_seeds()]),
+                    quote_tokens_left,
+                )?;
+
+                let clock = Clock::get()?;
+                let mut quote_reserve = quote_route.quote_reserve.load_mut()?;
+
+                quote_reserve.add_uncollected_fees(clock.slot, quote_tokens_left);
+                msg!(
+                    "{} quote tokens left over from the trade",
+                    quote_tokens_left
+                );
+            }
+        }
     }
 
     Ok(())
 }
 
 /// Verify that the amount of tokens we received for selling some collateral is acceptable
+///
+/// The proceeds are the loaned tokens received at the end of the swap, so any
+/// trade routed through the quote token is checked end-to-end.
 fn verify_proceeds(
     internal: &LiquidateDex,
     proceeds: u64,
@@ -637,30 +877,53 @@ fn update_accounting(
         .reserves()
         .get_cached(collateral_reserve.index, clock.slot);
 
-    let collateral_sell_expected = plan.collateral_sellable_value / collateral_info.price;
-    let collateral_repaid_ratio_actual = collateral_tokens_sold / collateral_sell_expected;
+    let loan_repaid_tokens = match plan.underwater {
+        true => {
+            // All the proceeds are used to repay as much of the loan as possible
+            let loan_tokens = loan_reserve.amount(token::accessor::amount(&internal.loan_account)?)
+                * loan_info.loan_note_exchange_rate;
+
+            std::cmp::min(loan_reserve.amount(proceeds), loan_tokens)
+        }
+        false => {
+            let collateral_sell_expected = plan.collateral_sellable_value / collateral_info.price;
+            let collateral_repaid_ratio_actual = collateral_tokens_sold / collateral_sell_expected;
 
-    let loan_repaid_value = collateral_repaid_ratio_actual * plan.loan_repay_value;
-    let loan_repaid_tokens = loan_repaid_value / loan_info.price;
+            let loan_repaid_value = collateral_repaid_ratio_actual * plan.loan_repay_value;
+            loan_repaid_value / loan_info.price
+        }
+    };
     let loan_repaid_tokens_u64 = loan_repaid_tokens.as_u64(loan_reserve.exponent);
-    let loan_repaid_notes = loan_repaid_tokens / loan_info.deposit_note_exchange_rate;
+    let loan_repaid_notes = loan_repaid_tokens / loan_info.loan_note_exchange_rate;
     let loan_repaid_notes_u64 = loan_repaid_notes.as_u64(loan_reserve.exponent);
 
     // Update the payment on the loan reserve
     loan_reserve.repay(clock.slot, loan_repaid_tokens_u64, loan_repaid_notes_u64);
 
-    // Update the changes in the obligation positions
-    let collateral_notes_sold = collateral_tokens_sold / collateral_info.deposit_note_exchange_rate;
+    // Round the notes for the sold collateral up, so that no fraction of a note
+    // is left behind in the obligation, but never beyond what the account holds.
+    let collateral_notes_sold = std::cmp::min(
+        (collateral_tokens_sold / collateral_info.deposit_note_exchange_rate)
+            .as_u64_ceil(collateral_reserve.exponent),
+        token::accessor::amount(&internal.collateral_account)?,
+    );
 
-    obligation.withdraw_collateral(internal.collateral_account.key, collateral_notes_sold)?;
-    obligation.repay(internal.loan_account.key, loan_repaid_notes)?;
+    // Update the changes in the obligation positions
+    obligation.withdraw_collateral(
+        internal.collateral_account.key,
+        collateral_reserve.amount(collateral_notes_sold),
+    )?;
+    obligation.repay(
+        internal.loan_account.key,
+        loan_reserve.amount(loan_repaid_notes_u64),
+    )?;
 
     // Burn the debt that's being repaid
     token::burn(
         internal
             .loan_note_burn_context()
             .with_signer(&[&market.authority_seeds()]),
-        loan_repaid_notes.as_u64(loan_reserve.exponent),
+        loan_repaid_notes_u64,
     )?;
 
     // Burn the collateral notes that were sold off
@@ -668,14 +931,36 @@ fn update_accounting(
         internal
             .collateral_note_burn_context()
             .with_signer(&[&market.authority_seeds()]),
-        collateral_notes_sold.as_u64(collateral_reserve.exponent),
+        collateral_notes_sold,
     )?;
 
     // Now to handle fees, where we've added extra tokens to the reserve vault
-    // that aren't applied to the debt. So we need to isolate these funds to be
-    // collected later.
+    // that aren't applied to the debt. A share goes to whoever performed the
+    // liquidation, and the rest needs to be isolated to be collected later.
+    // An underwater liquidation earns no premium, since all the proceeds go to
+    // repaying the debt, so no bounty is paid for it.
     let fee_proceeds = proceeds.saturating_sub(loan_repaid_tokens_u64);
-    loan_reserve.add_uncollected_fees(clock.slot, fee_proceeds);
+    let keeper_bounty = loan_reserve.keeper_bounty(fee_proceeds);
+
+    if keeper_bounty > 0 {
+        token::transfer(
+            internal
+                .keeper_bounty_transfer_context()
+                .with_signer(&[&market.authority_seeds()]),
+            keeper_bounty,
+        )?;
+    }
+
+    loan_reserve.add_uncollected_fees(clock.slot, fee_proceeds - keeper_bounty);
+
+    if plan.underwater && !obligation.has_collateral() {
+        // With no collateral left, the rest of the obligation's loans can never
+        // be repaid. They stay on their reserves for the market owner to write
+        // off, which records any shortfall the insurance fund can't cover.
+        let remaining_notes = token::accessor::amount(&internal.loan_account)?;
+
+        msg!("{} loan notes left to be written off", remaining_notes);
+    }
 
     Ok(())
 }
@@ -685,6 +970,7 @@ fn handler<'info>(
     source_market: &DexMarketAccounts<'info>,
     target_market: &DexMarketAccounts<'info>,
     internal: &LiquidateDex<'info>,
+    quote_route: Option<&QuoteRouteAccounts<'info>>,
 ) -> ProgramResult {
     // Only allow liquidations for unhealthy loans
     internal.verify_unhealthy()?;
@@ -699,10 +985,10 @@ fn handler<'info>(
     let loan_reserve_tokens = token::accessor::amount(&internal.loan_reserve_vault)?;
 
     // Calculate the quote value of collateral that needs to be sold
-    let plan = calculate_collateral_swap_plan(internal)?;
+    let plan = calculate_collateral_swap_plan(internal, quote_route)?;
 
     // Trade the the collateral
-    execute_plan(internal, source_market, target_market, &plan)?;
+    execute_plan(internal, source_market, target_market, quote_route, &plan)?;
 
     msg!("collateral sold");
 
@@ -736,6 +1022,10 @@ fn handler<'info>(
 /// work manually that anchor normally would generate automatically. In this case the
 /// generated code has some issues fitting within the stack frame limit, so to workaround
 /// that we just implement it here explicitly for now to ensure it fits within the frame.
+///
+/// When neither the collateral nor loan is the quote token, the quote token's reserve
+/// and its vault must be provided after the other accounts, so the trade can be routed
+/// through it.
 pub fn handler_raw<'info>(
     program_id: &Pubkey,
     accounts: &[AccountInfo<'info>],
@@ -749,9 +1039,22 @@ pub fn handler_raw<'info>(
     let source_market = DexMarketAccounts::try_accounts(program_id, &mut account_list, data)?;
     let target_market = DexMarketAccounts::try_accounts(program_id, &mut account_list, data)?;
     let liquidation = LiquidateDex::try_accounts(program_id, &mut account_list, data)?;
+    let quote_route = match account_list.is_empty() {
+        true => None,
+        false => Some(QuoteRouteAccounts::try_accounts(
+            program_id,
+            &mut account_list,
+            data,
+        )?),
+    };
 
     // pass accounts to real handler
-    handler(&source_market, &target_market, &liquidation)?;
+    handler(
+        &source_market,
+        &target_market,
+        &liquidation,
+        quote_route.as_ref(),
+    )?;
     Ok(())
 }
 
@@ -22,35 +22,68 @@ pub mod init_market;
 pub mod init_obligation;
 pub mod init_reserve;
 
+pub mod accept_market_owner;
+pub mod cancel_market_owner;
+pub mod pause_market;
+pub mod pause_reserve;
+pub mod propose_market_owner;
+pub mod set_asset_category;
 pub mod set_market_flags;
-pub mod set_market_owner;
+pub mod set_market_roles;
+pub mod set_market_treasury;
+pub mod set_reserve_category;
+pub mod set_reserve_config_delay;
+pub mod set_reserve_flags;
+pub mod set_reserve_secondary_oracle;
 
+pub mod clear_reserve_secondary_oracle;
 pub mod close_collateral_account;
 pub mod close_deposit_account;
 pub mod close_loan_account;
 pub mod close_obligation;
 
+pub mod apply_asset_category;
+pub mod apply_reserve_category;
+pub mod apply_reserve_config;
 pub mod borrow;
+pub mod cancel_reserve_config;
+pub mod collect_fees;
 pub mod deposit;
 pub mod deposit_collateral;
 pub mod deposit_tokens;
+pub mod flash_loan;
 pub mod liquidate;
 pub mod liquidate_dex;
 pub mod refresh_reserve;
+pub mod release_insurance_fund;
+pub mod remove_reserve;
 pub mod repay;
 pub mod update_reserve_config;
+pub mod update_reserve_dex_market;
+pub mod update_reserve_oracle;
+pub mod update_reserve_risk_config;
 pub mod withdraw;
 pub mod withdraw_collateral;
 pub mod withdraw_tokens;
+pub mod write_off_bad_debt;
 
+pub use accept_market_owner::*;
+pub use apply_asset_category::*;
+pub use apply_reserve_category::*;
+pub use apply_reserve_config::*;
 pub use borrow::*;
+pub use cancel_market_owner::*;
+pub use cancel_reserve_config::*;
+pub use clear_reserve_secondary_oracle::*;
 pub use close_collateral_account::*;
 pub use close_deposit_account::*;
 pub use close_loan_account::*;
 pub use close_obligation::*;
+pub use collect_fees::*;
 pub use deposit::*;
 pub use deposit_collateral::*;
 pub use deposit_tokens::*;
+pub use flash_loan::*;
 pub use init_collateral_account::*;
 pub use init_deposit_account::*;
 pub use init_loan_account::*;
@@ -59,11 +92,26 @@ pub use init_obligation::*;
 pub use init_reserve::*;
 pub use liquidate::*;
 pub use liquidate_dex::*;
+pub use pause_market::*;
+pub use pause_reserve::*;
+pub use propose_market_owner::*;
 pub use refresh_reserve::*;
+pub use release_insurance_fund::*;
+pub use remove_reserve::*;
 pub use repay::*;
+pub use set_asset_category::*;
 pub use set_market_flags::*;
-pub use set_market_owner::*;
+pub use set_market_roles::*;
+pub use set_market_treasury::*;
+pub use set_reserve_category::*;
+pub use set_reserve_config_delay::*;
+pub use set_reserve_flags::*;
+pub use set_reserve_secondary_oracle::*;
 pub use update_reserve_config::*;
+pub use update_reserve_dex_market::*;
+pub use update_reserve_oracle::*;
+pub use update_reserve_risk_config::*;
 pub use withdraw::*;
 pub use withdraw_collateral::*;
 pub use withdraw_tokens::*;
+pub use write_off_bad_debt::*;
@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Copyright (C) 2021 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+
+use crate::errors::ErrorCode;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct PauseReserve<'info> {
+    #[account(has_one = pause_guardian)]
+    pub market: Loader<'info, Market>,
+
+    #[account(mut, has_one = market)]
+    pub reserve: Loader<'info, Reserve>,
+
+    #[account(signer)]
+    pub pause_guardian: AccountInfo<'info>,
+}
+
+/// Halt operations on a reserve, in addition to any already halted
+pub fn handler(ctx: Context<PauseReserve>, flags: u64) -> ProgramResult {
+    let mut reserve = ctx.accounts.reserve.load_mut()?;
+    reserve.verify_unlocked()?;
+
+    let flags = match ReserveFlags::from_bits(flags) {
+        Some(f) if ReserveFlags::HALT_ALL.contains(f) => f,
+        _ => return Err(ErrorCode::InvalidParameter.into()),
+    };
+
+    let current = reserve.flags();
+    reserve.reset_flags(current | flags);
+
+    Ok(())
+}
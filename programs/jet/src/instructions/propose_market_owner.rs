@@ -20,7 +20,7 @@ use anchor_lang::prelude::*;
 use crate::state::*;
 
 #[derive(Accounts)]
-pub struct SetMarketOwner<'info> {
+pub struct ProposeMarketOwner<'info> {
     #[account(mut, has_one = owner)]
     pub market: Loader<'info, Market>,
 
@@ -28,10 +28,10 @@ pub struct SetMarketOwner<'info> {
     pub owner: AccountInfo<'info>,
 }
 
-/// Change the owner on a market
-pub fn handler(ctx: Context<SetMarketOwner>, new_owner: Pubkey) -> ProgramResult {
+/// Propose a new owner for a market
+pub fn handler(ctx: Context<ProposeMarketOwner>, new_owner: Pubkey) -> ProgramResult {
     let mut market = ctx.accounts.market.load_mut()?;
-    market.owner = new_owner;
+    market.pending_owner = new_owner;
 
     Ok(())
 }
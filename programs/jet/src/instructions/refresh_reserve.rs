@@ -18,9 +18,8 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, MintTo};
 use jet_math::Number;
-use pyth_client::Price;
+use pyth_client::{Price, PriceStatus};
 
-use crate::utils::JobCompletion;
 use crate::{errors::ErrorCode, state::*};
 
 #[derive(Accounts)]
@@ -70,73 +69,194 @@ impl<'info> RefreshReserve<'info> {
 pub fn handler(ctx: Context<RefreshReserve>) -> ProgramResult {
     let mut market = ctx.accounts.market.load_mut()?;
     let mut reserve = ctx.accounts.reserve.load_mut()?;
+    reserve.verify_unlocked()?;
 
-    let oracle_data = ctx.accounts.pyth_oracle_price.try_borrow_data()?;
+    let clock = Clock::get()?;
+    let config = reserve.config;
+    let primary_price = read_oracle_price(&ctx.accounts.pyth_oracle_price, &config, clock.slot);
+
+    // A reserve with a secondary oracle expects it as the first remaining account
+    let price = match reserve.secondary_oracle_price {
+        key if key == Pubkey::default() => primary_price?,
+        key => {
+            let secondary_oracle_price = match ctx.remaining_accounts.first() {
+                Some(account) if account.key() == key => account,
+                _ => {
+                    msg!("the reserve's secondary oracle account must be provided");
+                    return Err(ErrorCode::InvalidOracle.into());
+                }
+            };
+
+            let secondary_price = read_oracle_price(secondary_oracle_price, &config, clock.slot);
+            select_oracle_price(primary_price, secondary_price, config.oracle_max_deviation)?
+        }
+    };
+
+    let category = market.asset_category(reserve.category);
+
+    reserve.record_price(clock.slot, price.price);
+
+    let market_reserves = market.reserves_mut();
+
+    market_reserves.verify_registered(reserve.index, &ctx.accounts.reserve.key())?;
+
+    let reserve_info = market_reserves.get_mut(reserve.index);
+
+    let vault_amount = reserve.total_deposits();
+    let deposit_note_mint_supply = reserve.total_deposit_notes();
+    let loan_note_mint_supply = reserve.total_loan_notes();
+
+    // apply the interest for outstanding debt on this reserve
+    reserve.try_accrue_interest(vault_amount, clock.unix_timestamp, clock.slot)?;
+
+    // record the current value of the loan and deposit notes, as a way of
+    // mitigating problems with undervaluing collateral in a liquidation.
+    // this needs to come after interest accrual (Cache should ensure this)
+    let reserve_cache = reserve_info.get_stale_mut();
+    let deposit_note_exchange_rate =
+        reserve.deposit_note_exchange_rate(clock.slot, vault_amount, deposit_note_mint_supply);
+    let loan_note_exchange_rate =
+        reserve.loan_note_exchange_rate(clock.slot, loan_note_mint_supply);
+
+    reserve_cache.price = price.price;
+    reserve_cache.collateral_price = price.collateral_price;
+    reserve_cache.debt_price = price.debt_price;
+
+    reserve_cache.deposit_note_exchange_rate = deposit_note_exchange_rate;
+    reserve_cache.loan_note_exchange_rate = loan_note_exchange_rate;
+    reserve_cache.min_collateral_ratio = Number::from_bps(reserve.config.min_collateral_ratio);
+    reserve_cache.liquidation_collateral_ratio =
+        Number::from_bps(reserve.liquidation_collateral_ratio());
+    reserve_cache.collateral_factor = Number::from_bps(reserve.collateral_factor());
+    reserve_cache.liquidation_bonus = reserve.config.liquidation_premium;
+
+    match category {
+        Some(params) => {
+            reserve_cache.category = reserve.category;
+            reserve_cache.category_min_collateral_ratio =
+                Number::from_bps(params.min_collateral_ratio);
+            reserve_cache.category_liquidation_collateral_ratio =
+                Number::from_bps(params.liquidation_collateral_ratio());
+            reserve_cache.category_liquidation_bonus = params.liquidation_premium;
+        }
+        None => reserve_cache.category = 0,
+    }
+
+    // record current time
+    reserve_info.refresh_to(clock.slot);
+
+    // Collect any fees that need to be minted to notes
+    let notes_to_mint = reserve.collect_accrued_fees(clock.slot, deposit_note_exchange_rate);
+
+    if notes_to_mint > 0 {
+        token::mint_to(
+            ctx.accounts
+                .fee_note_mint_context()
+                .with_signer(&[&market.authority_seeds()]),
+            notes_to_mint,
+        )?;
+    }
+
+    msg!("reserve refreshed");
+    Ok(())
+}
+
+/// The prices for a token, as read from an oracle
+struct OraclePrice {
+    /// The aggregate price
+    price: Number,
+
+    /// The lower of the confidence interval's bottom and the time-weighted
+    /// price, used to value the token as collateral
+    collateral_price: Number,
+
+    /// The higher of the confidence interval's top and the time-weighted
+    /// price, used to value the token as debt
+    debt_price: Number,
+}
+
+/// Read the current price from a Pyth price account, checking that it's
+/// recent, trading and confident enough to be used.
+fn read_oracle_price(
+    oracle_price: &AccountInfo,
+    config: &ReserveConfig,
+    current_slot: u64,
+) -> Result<OraclePrice, ProgramError> {
+    let oracle_data = oracle_price.try_borrow_data()?;
     let oracle = pyth_client::cast::<Price>(&oracle_data);
-    if oracle.agg.price < 0 {
+
+    if !matches!(oracle.agg.status, PriceStatus::Trading) {
+        msg!("pyth price is not currently trading");
+        return Err(ErrorCode::StaleOracle.into());
+    }
+
+    let max_age = config.max_oracle_age as u64;
+    if max_age != 0 && current_slot.saturating_sub(oracle.agg.pub_slot) > max_age {
+        msg!(
+            "pyth price was last published at slot {}",
+            oracle.agg.pub_slot
+        );
+        return Err(ErrorCode::StaleOracle.into());
+    }
+
+    if oracle.agg.price < 0 || oracle.twap.val < 0 {
         return Err(ErrorCode::InvalidOraclePrice.into());
     }
-    let threshold = Number::from_bps(reserve.config.confidence_threshold)
+    let threshold = Number::from_bps(config.confidence_threshold)
         * Number::from_decimal(oracle.twap.val, oracle.expo);
     if oracle.agg.conf > threshold.as_u64_ceil(oracle.expo) {
         msg!("pyth confidence range outside threshold");
         return Err(ErrorCode::InvalidOraclePrice.into());
     }
 
-    let market_reserves = market.reserves_mut();
-    let reserve_info = market_reserves.get_mut(reserve.index);
+    let price = Number::from_decimal(oracle.agg.price, oracle.expo);
+    let confidence = Number::from_decimal(oracle.agg.conf, oracle.expo);
+    let twap = Number::from_decimal(oracle.twap.val, oracle.expo);
 
-    let clock = Clock::get()?;
+    Ok(OraclePrice {
+        price,
+        collateral_price: std::cmp::min(price.saturating_sub(confidence), twap),
+        debt_price: std::cmp::max(price + confidence, twap),
+    })
+}
 
-    let vault_amount = reserve.total_deposits();
-    let deposit_note_mint_supply = reserve.total_deposit_notes();
-    let loan_note_mint_supply = reserve.total_loan_notes();
+/// Choose between the prices read from a primary and secondary oracle. The
+/// primary price is used when they agree, and the secondary only when the
+/// primary is stale. Any other problem with the primary price is an error.
+fn select_oracle_price(
+    primary: Result<OraclePrice, ProgramError>,
+    secondary: Result<OraclePrice, ProgramError>,
+    max_deviation: u16,
+) -> Result<OraclePrice, ProgramError> {
+    match (primary, secondary) {
+        (Ok(primary), Ok(secondary)) => {
+            let difference = match primary.price > secondary.price {
+                true => primary.price - secondary.price,
+                false => secondary.price - primary.price,
+            };
 
-    // apply the interest for outstanding debt on this reserve
-    match reserve.try_accrue_interest(vault_amount, clock.unix_timestamp, clock.slot) {
-        JobCompletion::Partial => {
-            msg!("performing partial reserve refresh: additional iterations required");
-            reserve_info.invalidate();
-        }
-        JobCompletion::Full => {
-            // record the current value of the loan and deposit notes, as a way of
-            // mitigating problems with undervaluing collateral in a liquidation.
-            // this needs to come after interest accrual (Cache should ensure this)
-            let reserve_cache = reserve_info.get_stale_mut();
-            let deposit_note_exchange_rate = reserve.deposit_note_exchange_rate(
-                clock.slot,
-                vault_amount,
-                deposit_note_mint_supply,
-            );
-            let loan_note_exchange_rate =
-                reserve.loan_note_exchange_rate(clock.slot, loan_note_mint_supply);
-
-            reserve_cache.price = Number::from_decimal(oracle.agg.price, oracle.expo);
-
-            reserve_cache.deposit_note_exchange_rate = deposit_note_exchange_rate;
-            reserve_cache.loan_note_exchange_rate = loan_note_exchange_rate;
-            reserve_cache.min_collateral_ratio =
-                Number::from_bps(reserve.config.min_collateral_ratio);
-            reserve_cache.liquidation_bonus = reserve.config.liquidation_premium;
-
-            // record current time
-            reserve_info.refresh_to(clock.slot);
-
-            // Collect any fees that need to be minted to notes
-            let notes_to_mint =
-                reserve.collect_accrued_fees(clock.slot, deposit_note_exchange_rate);
-
-            if notes_to_mint > 0 {
-                token::mint_to(
-                    ctx.accounts
-                        .fee_note_mint_context()
-                        .with_signer(&[&market.authority_seeds()]),
-                    notes_to_mint,
-                )?;
+            if max_deviation != 0 && difference > Number::from_bps(max_deviation) * primary.price {
+                msg!(
+                    "primary price {} differs from secondary price {}",
+                    primary.price,
+                    secondary.price
+                );
+                return Err(ErrorCode::OracleDeviation.into());
             }
 
-            msg!("reserve refreshed");
+            Ok(primary)
+        }
+        (Ok(primary), Err(e)) => {
+            msg!(
+                "secondary oracle unavailable ({}), using the primary price without comparing",
+                e
+            );
+            Ok(primary)
+        }
+        (Err(e), Ok(secondary)) if e == ErrorCode::StaleOracle.into() => {
+            msg!("primary oracle is stale, using the secondary oracle");
+            Ok(secondary)
         }
+        (Err(e), _) => Err(e),
     }
-    Ok(())
 }
@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Copyright (C) 2021 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Key;
+use anchor_spl::token::{self, TokenAccount, Transfer};
+
+use crate::errors::ErrorCode;
+use crate::state::*;
+
+#[event]
+pub struct InsuranceFundReleased {
+    reserve: Pubkey,
+    treasury_account: Pubkey,
+    amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseInsuranceFund<'info> {
+    /// The market the reserve is in
+    #[account(has_one = owner,
+              has_one = market_authority)]
+    pub market: Loader<'info, Market>,
+
+    /// The market's authority account, which owns the vault
+    pub market_authority: AccountInfo<'info>,
+
+    /// The reserve holding the insurance fund
+    #[account(mut,
+              has_one = market,
+              has_one = vault,
+              constraint = reserve.load().unwrap().token_mint == treasury_account.mint)]
+    pub reserve: Loader<'info, Reserve>,
+
+    /// The reserve's vault, which holds the fund's tokens
+    #[account(mut)]
+    pub vault: AccountInfo<'info>,
+
+    /// The treasury's account to receive the tokens
+    #[account(mut)]
+    pub treasury_account: Account<'info, TokenAccount>,
+
+    /// The owner of the market
+    #[account(signer)]
+    pub owner: AccountInfo<'info>,
+
+    #[account(address = token::ID)]
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> ReleaseInsuranceFund<'info> {
+    fn transfer_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.clone(),
+            Transfer {
+                from: self.vault.clone(),
+                to: self.treasury_account.to_account_info(),
+                authority: self.market_authority.clone(),
+            },
+        )
+    }
+}
+
+/// Move tokens out of a reserve's insurance fund to the market's treasury
+pub fn handler(ctx: Context<ReleaseInsuranceFund>, amount: u64) -> ProgramResult {
+    let clock = Clock::get()?;
+    let market = ctx.accounts.market.load()?;
+    let mut reserve = ctx.accounts.reserve.load_mut()?;
+    reserve.verify_unlocked()?;
+
+    market
+        .reserves()
+        .verify_registered(reserve.index, &ctx.accounts.reserve.key())?;
+
+    if market.fee_treasury == Pubkey::default()
+        || ctx.accounts.treasury_account.owner != market.fee_treasury
+    {
+        return Err(ErrorCode::InvalidFeeTreasury.into());
+    }
+
+    reserve.release_insurance(clock.slot, amount)?;
+
+    token::transfer(
+        ctx.accounts
+            .transfer_context()
+            .with_signer(&[&market.authority_seeds()]),
+        amount,
+    )?;
+
+    emit!(InsuranceFundReleased {
+        reserve: ctx.accounts.reserve.key(),
+        treasury_account: ctx.accounts.treasury_account.key(),
+        amount
+    });
+
+    msg!("released {} tokens from the insurance fund", amount);
+    Ok(())
+}
@@ -0,0 +1,184 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Copyright (C) 2021 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+use anchor_spl::dex::{self, CloseOpenOrders};
+use anchor_spl::token::{self, CloseAccount, TokenAccount, Transfer};
+
+use crate::errors::ErrorCode;
+use crate::state::*;
+use crate::utils::verify_account_empty;
+
+#[derive(Accounts)]
+pub struct RemoveReserve<'info> {
+    /// The market the reserve is being removed from
+    #[account(mut,
+              has_one = owner,
+              has_one = market_authority)]
+    pub market: Loader<'info, Market>,
+
+    /// The market's authority account, which owns the vaults
+    pub market_authority: AccountInfo<'info>,
+
+    /// The reserve being removed
+    #[account(has_one = market,
+              has_one = token_mint,
+              has_one = vault,
+              has_one = fee_note_vault,
+              has_one = dex_swap_tokens,
+              has_one = dex_open_orders,
+              has_one = dex_market)]
+    pub reserve: Loader<'info, Reserve>,
+
+    /// The reserve's vault for storing deposited tokens
+    #[account(mut)]
+    pub vault: AccountInfo<'info>,
+
+    /// The reserve's vault for storing collected fees
+    #[account(mut)]
+    pub fee_note_vault: AccountInfo<'info>,
+
+    /// The reserve's account for holding tokens being swapped on the DEX
+    #[account(mut)]
+    pub dex_swap_tokens: AccountInfo<'info>,
+
+    /// The reserve's account for placing orders on the DEX
+    #[account(mut)]
+    pub dex_open_orders: AccountInfo<'info>,
+
+    /// The DEX market the reserve's open orders account belongs to
+    pub dex_market: AccountInfo<'info>,
+
+    /// The mint for the reserve's token
+    pub token_mint: AccountInfo<'info>,
+
+    /// The treasury's account to receive any tokens left in the vault
+    #[account(mut,
+              constraint = treasury_account.mint == token_mint.key())]
+    pub treasury_account: Account<'info, TokenAccount>,
+
+    /// The owner of the market
+    #[account(signer)]
+    pub owner: AccountInfo<'info>,
+
+    /// The account to receive the rent from the closed accounts
+    #[account(mut)]
+    pub receiver: AccountInfo<'info>,
+
+    #[account(address = token::ID)]
+    pub token_program: AccountInfo<'info>,
+
+    #[account(address = dex::ID)]
+    pub dex_program: AccountInfo<'info>,
+}
+
+impl<'info> RemoveReserve<'info> {
+    fn transfer_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.clone(),
+            Transfer {
+                from: self.vault.clone(),
+                to: self.treasury_account.to_account_info(),
+                authority: self.market_authority.clone(),
+            },
+        )
+    }
+
+    fn close_context(
+        &self,
+        account: &AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        CpiContext::new(
+            self.token_program.clone(),
+            CloseAccount {
+                account: account.clone(),
+                destination: self.receiver.clone(),
+                authority: self.market_authority.clone(),
+            },
+        )
+    }
+
+    fn close_open_orders_context(&self) -> CpiContext<'_, '_, '_, 'info, CloseOpenOrders<'info>> {
+        CpiContext::new(
+            self.dex_program.clone(),
+            CloseOpenOrders {
+                open_orders: self.dex_open_orders.clone(),
+                authority: self.market_authority.clone(),
+                destination: self.receiver.clone(),
+                market: self.dex_market.clone(),
+            },
+        )
+    }
+}
+
+/// Remove a deprecated reserve from its market, once it holds no more deposits or loans.
+/// Any tokens left in the vault, such as rounding dust, uncollected fees and the
+/// insurance fund, are moved to the market's treasury.
+pub fn handler(ctx: Context<RemoveReserve>) -> ProgramResult {
+    let mut market = ctx.accounts.market.load_mut()?;
+    let reserve = ctx.accounts.reserve.load()?;
+    reserve.verify_unlocked()?;
+
+    reserve.verify_removable(token::accessor::amount(&ctx.accounts.fee_note_vault)?)?;
+    market
+        .reserves()
+        .verify_registered(reserve.index, &ctx.accounts.reserve.key())?;
+
+    if market.fee_treasury == Pubkey::default()
+        || ctx.accounts.treasury_account.owner != market.fee_treasury
+    {
+        return Err(ErrorCode::InvalidFeeTreasury.into());
+    }
+
+    verify_account_empty(&ctx.accounts.dex_swap_tokens)?;
+
+    let residual_tokens = token::accessor::amount(&ctx.accounts.vault)?;
+
+    if residual_tokens > 0 {
+        token::transfer(
+            ctx.accounts
+                .transfer_context()
+                .with_signer(&[&market.authority_seeds()]),
+            residual_tokens,
+        )?;
+
+        msg!("moved {} residual tokens to the treasury", residual_tokens);
+    }
+
+    for account in [
+        &ctx.accounts.vault,
+        &ctx.accounts.fee_note_vault,
+        &ctx.accounts.dex_swap_tokens,
+    ] {
+        token::close_account(
+            ctx.accounts
+                .close_context(account)
+                .with_signer(&[&market.authority_seeds()]),
+        )?;
+    }
+
+    dex::close_open_orders(
+        ctx.accounts
+            .close_open_orders_context()
+            .with_signer(&[&market.authority_seeds()]),
+    )?;
+
+    market.reserves_mut().remove(reserve.index);
+
+    msg!("removed reserve from market");
+    Ok(())
+}
@@ -165,9 +165,14 @@ pub fn repay<'info, T: RepayContext<'info>>(
     let mut reserve = ctx.accounts.reserve().load_mut()?;
     let mut obligation = ctx.accounts.obligation().load_mut()?;
     let loan_account = ctx.accounts.loan_account();
+
+    market
+        .reserves()
+        .verify_registered(reserve.index, &ctx.accounts.reserve().key())?;
+
     let reserve_info = market.reserves().get_cached(reserve.index, clock.slot);
 
-    market.verify_ability_repay()?;
+    market.verify_ability_repay(&reserve)?;
 
     // Calculate the number of tokens and notes that match the value being repaid
     let payoff_notes = amount.as_loan_notes(reserve_info, Rounding::Down)?;
@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Copyright (C) 2021 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+
+use crate::errors::ErrorCode;
+use crate::state::*;
+
+#[event]
+pub struct AssetCategoryQueued {
+    pub market: Pubkey,
+    pub category: u16,
+    pub activation_time: i64,
+}
+
+#[derive(Accounts)]
+pub struct SetAssetCategory<'info> {
+    #[account(mut, has_one = owner)]
+    pub market: Loader<'info, Market>,
+
+    #[account(signer)]
+    pub owner: AccountInfo<'info>,
+}
+
+/// Define the risk parameters for an asset category in a market, or queue
+/// the change if the market requires a delay
+pub fn handler(
+    ctx: Context<SetAssetCategory>,
+    category: u16,
+    params: AssetCategory,
+) -> ProgramResult {
+    let mut market = ctx.accounts.market.load_mut()?;
+    let clock = Clock::get()?;
+
+    params.validate()?;
+
+    let delay = market.reserve_config_delay(clock.unix_timestamp);
+
+    // Without a delay configured on the market, the change takes effect immediately
+    if delay == 0 {
+        market.set_asset_category(category, params)?;
+        market.cancel_pending_asset_category();
+        return Ok(());
+    }
+
+    let activation_time = clock
+        .unix_timestamp
+        .checked_add(delay)
+        .ok_or(ErrorCode::ArithmeticError)?;
+    market.queue_asset_category(category, params, activation_time)?;
+
+    emit!(AssetCategoryQueued {
+        market: ctx.accounts.market.key(),
+        category,
+        activation_time,
+    });
+
+    Ok(())
+}
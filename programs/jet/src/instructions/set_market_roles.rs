@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Copyright (C) 2021 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+
+use crate::errors::ErrorCode;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct SetMarketRoles<'info> {
+    #[account(mut, has_one = owner)]
+    pub market: Loader<'info, Market>,
+
+    #[account(signer)]
+    pub owner: AccountInfo<'info>,
+}
+
+/// Assign the accounts with limited authority over the market
+pub fn handler(
+    ctx: Context<SetMarketRoles>,
+    pause_guardian: Pubkey,
+    risk_admin: Pubkey,
+    risk_admin_max_change: u16,
+) -> ProgramResult {
+    let mut market = ctx.accounts.market.load_mut()?;
+
+    if risk_admin_max_change > 10_000 {
+        msg!("risk_admin_max_change cannot be above 100%");
+        return Err(ErrorCode::InvalidParameter.into());
+    }
+
+    market.pause_guardian = pause_guardian;
+    market.risk_admin = risk_admin;
+    market.risk_admin_max_change = risk_admin_max_change;
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Copyright (C) 2021 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+
+use crate::errors::ErrorCode;
+use crate::state::*;
+
+#[event]
+pub struct ReserveCategoryQueued {
+    pub reserve: Pubkey,
+    pub category: u16,
+    pub activation_time: i64,
+}
+
+#[derive(Accounts)]
+pub struct SetReserveCategory<'info> {
+    #[account(has_one = owner)]
+    pub market: Loader<'info, Market>,
+
+    #[account(mut, has_one = market)]
+    pub reserve: Loader<'info, Reserve>,
+
+    #[account(signer)]
+    pub owner: AccountInfo<'info>,
+}
+
+/// Assign a reserve to an asset category, or remove it from one with zero.
+/// The change is queued if the market requires a delay.
+pub fn handler(ctx: Context<SetReserveCategory>, category: u16) -> ProgramResult {
+    let market = ctx.accounts.market.load()?;
+    let mut reserve = ctx.accounts.reserve.load_mut()?;
+    reserve.verify_unlocked()?;
+
+    if category as usize >= MAX_ASSET_CATEGORIES {
+        return Err(ErrorCode::InvalidParameter.into());
+    }
+
+    let clock = Clock::get()?;
+    let delay = market.reserve_config_delay(clock.unix_timestamp);
+
+    // Without a delay configured on the market, the change takes effect immediately
+    if delay == 0 {
+        reserve.category = category;
+        reserve.cancel_pending_category();
+        return Ok(());
+    }
+
+    let activation_time = clock
+        .unix_timestamp
+        .checked_add(delay)
+        .ok_or(ErrorCode::ArithmeticError)?;
+    reserve.queue_category(category, activation_time);
+
+    emit!(ReserveCategoryQueued {
+        reserve: ctx.accounts.reserve.key(),
+        category,
+        activation_time,
+    });
+
+    Ok(())
+}
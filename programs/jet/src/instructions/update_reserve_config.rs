@@ -1,6 +1,13 @@
+use crate::errors::ErrorCode;
 use crate::state::*;
 use anchor_lang::prelude::*;
 
+#[event]
+pub struct ReserveConfigQueued {
+    pub reserve: Pubkey,
+    pub activation_time: i64,
+}
+
 #[derive(Accounts)]
 pub struct UpdateReserveConfig<'info> {
     #[account(has_one = owner)]
@@ -14,7 +21,49 @@ pub struct UpdateReserveConfig<'info> {
 }
 
 pub fn handler(ctx: Context<UpdateReserveConfig>, new_config: ReserveConfig) -> ProgramResult {
+    let market = ctx.accounts.market.load()?;
     let mut reserve = ctx.accounts.reserve.load_mut()?;
-    reserve.config = new_config;
+    reserve.verify_unlocked()?;
+
+    new_config.validate()?;
+    stage_config(
+        &market,
+        &mut reserve,
+        ctx.accounts.reserve.key(),
+        new_config,
+    )
+}
+
+/// Apply a new config to a reserve, or queue it if the market requires a delay
+pub(crate) fn stage_config(
+    market: &Market,
+    reserve: &mut Reserve,
+    reserve_key: Pubkey,
+    new_config: ReserveConfig,
+) -> ProgramResult {
+    reserve.verify_oracle_config(&new_config)?;
+
+    let clock = Clock::get()?;
+
+    let delay = market.reserve_config_delay(clock.unix_timestamp);
+
+    // Without a delay configured on the market, the change takes effect immediately
+    if delay == 0 {
+        reserve.config = new_config;
+        reserve.cancel_pending_config();
+        return Ok(());
+    }
+
+    let activation_time = clock
+        .unix_timestamp
+        .checked_add(delay)
+        .ok_or(ErrorCode::ArithmeticError)?;
+    reserve.queue_config(new_config, activation_time);
+
+    emit!(ReserveConfigQueued {
+        reserve: reserve_key,
+        activation_time,
+    });
+
     Ok(())
 }
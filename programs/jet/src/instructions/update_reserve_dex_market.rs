@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Copyright (C) 2021 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use anchor_lang::prelude::*;
+use anchor_lang::Key;
+use anchor_spl::dex::serum_dex::state::OpenOrders;
+use anchor_spl::dex::{self, CloseOpenOrders, InitOpenOrders, SettleFunds};
+use anchor_spl::token;
+
+use crate::errors::ErrorCode;
+use crate::state::*;
+use crate::utils;
+
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct UpdateReserveDexMarket<'info> {
+    /// The market the reserve belongs to
+    #[account(has_one = owner,
+              has_one = market_authority)]
+    pub market: Loader<'info, Market>,
+
+    /// The market's authority account, which owns the open orders
+    pub market_authority: AccountInfo<'info>,
+
+    /// The reserve being moved to the new DEX market
+    #[account(mut,
+              has_one = market,
+              has_one = vault,
+              has_one = dex_swap_tokens,
+              has_one = dex_open_orders,
+              has_one = dex_market)]
+    pub reserve: Loader<'info, Reserve>,
+
+    /// The reserve's vault, which receives any unsettled reserve tokens
+    #[account(mut)]
+    pub vault: AccountInfo<'info>,
+
+    /// The reserve's account for quote tokens, which receives any unsettled quote tokens
+    #[account(mut)]
+    pub dex_swap_tokens: AccountInfo<'info>,
+
+    /// The reserve's current open orders account, which is closed
+    #[account(mut)]
+    pub dex_open_orders: AccountInfo<'info>,
+
+    /// The DEX market the reserve currently uses
+    #[account(mut)]
+    pub dex_market: AccountInfo<'info>,
+
+    /// The current DEX market's vault for the "base" currency
+    #[account(mut)]
+    pub dex_coin_vault: AccountInfo<'info>,
+
+    /// The current DEX market's vault for the "quote" currency
+    #[account(mut)]
+    pub dex_pc_vault: AccountInfo<'info>,
+
+    /// The current DEX market's vault owner
+    pub dex_vault_signer: AccountInfo<'info>,
+
+    /// The DEX market the reserve will use to trade its asset
+    pub new_dex_market: AccountInfo<'info>,
+
+    /// The account to use for placing orders on the new DEX market
+    #[account(init,
+              seeds = [
+                  b"dex-open-orders".as_ref(),
+                  reserve.key().as_ref(),
+                  new_dex_market.key().as_ref()
+              ],
+              bump = bump,
+              payer = owner,
+              owner = dex::ID,
+              space = std::mem::size_of::<OpenOrders>() + 12)]
+    pub new_dex_open_orders: AccountInfo<'info>,
+
+    /// The market owner, which must sign to make this change to the reserve.
+    #[account(mut, signer)]
+    pub owner: AccountInfo<'info>,
+
+    /// The account to receive the rent from the closed open orders
+    #[account(mut)]
+    pub receiver: AccountInfo<'info>,
+
+    #[account(address = token::ID)]
+    pub token_program: AccountInfo<'info>,
+
+    #[account(address = dex::ID)]
+    pub dex_program: AccountInfo<'info>,
+
+    pub system_program: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> UpdateReserveDexMarket<'info> {
+    fn settle_funds_context(&self) -> CpiContext<'_, '_, '_, 'info, SettleFunds<'info>> {
+        CpiContext::new(
+            self.dex_program.clone(),
+            SettleFunds {
+                market: self.dex_market.clone(),
+                open_orders: self.dex_open_orders.clone(),
+                open_orders_authority: self.market_authority.clone(),
+                coin_vault: self.dex_coin_vault.clone(),
+                pc_vault: self.dex_pc_vault.clone(),
+                coin_wallet: self.vault.clone(),
+                pc_wallet: self.dex_swap_tokens.clone(),
+                vault_signer: self.dex_vault_signer.clone(),
+                token_program: self.token_program.clone(),
+            },
+        )
+    }
+
+    fn close_open_orders_context(&self) -> CpiContext<'_, '_, '_, 'info, CloseOpenOrders<'info>> {
+        CpiContext::new(
+            self.dex_program.clone(),
+            CloseOpenOrders {
+                open_orders: self.dex_open_orders.clone(),
+                authority: self.market_authority.clone(),
+                destination: self.receiver.clone(),
+                market: self.dex_market.clone(),
+            },
+        )
+    }
+
+    fn init_open_orders_context(&self) -> CpiContext<'_, '_, '_, 'info, InitOpenOrders<'info>> {
+        CpiContext::new(
+            self.dex_program.clone(),
+            InitOpenOrders {
+                open_orders: self.new_dex_open_orders.clone(),
+                authority: self.market_authority.clone(),
+                market: self.new_dex_market.clone(),
+                rent: self.rent.to_account_info(),
+            },
+        )
+    }
+}
+
+/// Move a reserve to a new DEX market, replacing its open orders account
+pub fn handler(ctx: Context<UpdateReserveDexMarket>, _bump: u8) -> ProgramResult {
+    let market = ctx.accounts.market.load()?;
+    let mut reserve = ctx.accounts.reserve.load_mut()?;
+    reserve.verify_unlocked()?;
+
+    if reserve.token_mint == market.quote_token_mint {
+        msg!("the quote token reserve does not trade on a DEX market");
+        return Err(ErrorCode::InvalidParameter.into());
+    }
+
+    utils::verify_dex_market_tokens(
+        &ctx.accounts.new_dex_market,
+        ctx.accounts.dex_program.key,
+        &reserve.token_mint,
+        &market.quote_token_mint,
+    )?;
+
+    // Return anything left on the old market to the reserve before closing it
+    dex::settle_funds(
+        ctx.accounts
+            .settle_funds_context()
+            .with_signer(&[&market.authority_seeds()]),
+    )?;
+
+    dex::close_open_orders(
+        ctx.accounts
+            .close_open_orders_context()
+            .with_signer(&[&market.authority_seeds()]),
+    )?;
+
+    dex::init_open_orders(
+        ctx.accounts
+            .init_open_orders_context()
+            .with_signer(&[&market.authority_seeds()]),
+    )?;
+
+    reserve.dex_market = ctx.accounts.new_dex_market.key();
+    reserve.dex_open_orders = ctx.accounts.new_dex_open_orders.key();
+
+    msg!("updated dex market for reserve #{}", { reserve.index });
+    Ok(())
+}
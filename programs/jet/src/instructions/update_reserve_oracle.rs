@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Copyright (C) 2021 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+use pyth_client::Product;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct UpdateReserveOracle<'info> {
+    #[account(has_one = owner)]
+    pub market: Loader<'info, Market>,
+
+    #[account(mut, has_one = market)]
+    pub reserve: Loader<'info, Reserve>,
+
+    #[account(signer)]
+    pub owner: AccountInfo<'info>,
+
+    /// The account containing the price information for the token.
+    pub oracle_price: AccountInfo<'info>,
+
+    /// The account containing the metadata about the token being referenced
+    pub oracle_product: AccountInfo<'info>,
+}
+
+/// Change the primary oracle accounts used by a reserve
+pub fn handler(ctx: Context<UpdateReserveOracle>) -> ProgramResult {
+    let market = ctx.accounts.market.load()?;
+    let mut reserve = ctx.accounts.reserve.load_mut()?;
+    reserve.verify_unlocked()?;
+
+    let product_data = ctx.accounts.oracle_product.try_borrow_data()?;
+    let product = pyth_client::cast::<Product>(&product_data);
+
+    market.validate_oracle(product, ctx.accounts.oracle_price.key)?;
+
+    reserve.pyth_oracle_price = ctx.accounts.oracle_price.key();
+    reserve.pyth_oracle_product = ctx.accounts.oracle_product.key();
+
+    msg!("updated oracle for reserve #{}", { reserve.index });
+    Ok(())
+}
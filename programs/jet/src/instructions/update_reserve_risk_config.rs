@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Copyright (C) 2021 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+
+use crate::errors::ErrorCode;
+use crate::instructions::update_reserve_config::stage_config;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct UpdateReserveRiskConfig<'info> {
+    #[account(has_one = risk_admin)]
+    pub market: Loader<'info, Market>,
+
+    #[account(mut, has_one = market)]
+    pub reserve: Loader<'info, Reserve>,
+
+    #[account(signer)]
+    pub risk_admin: AccountInfo<'info>,
+}
+
+/// Change the risk parameters in a reserve's config
+pub fn handler(ctx: Context<UpdateReserveRiskConfig>, new_config: ReserveConfig) -> ProgramResult {
+    let market = ctx.accounts.market.load()?;
+    let mut reserve = ctx.accounts.reserve.load_mut()?;
+    reserve.verify_unlocked()?;
+
+    let current_config = reserve.config;
+
+    // Don't replace a change the owner has already queued
+    if reserve.pending_config_activation != 0 {
+        return Err(ErrorCode::ReserveConfigPending.into());
+    }
+
+    current_config.verify_risk_change(&new_config, market.risk_admin_max_change)?;
+    new_config.validate()?;
+
+    stage_config(
+        &market,
+        &mut reserve,
+        ctx.accounts.reserve.key(),
+        new_config,
+    )
+}
@@ -111,9 +111,14 @@ pub fn handler(
     let market = ctx.accounts.market.load()?;
     let reserve = ctx.accounts.reserve.load()?;
     let clock = Clock::get()?;
+
+    market
+        .reserves()
+        .verify_registered(reserve.index, &ctx.accounts.reserve.key())?;
+
     let reserve_info = market.reserves().get_cached(reserve.index, clock.slot);
 
-    market.verify_ability_borrow()?;
+    market.verify_ability_borrow(&reserve)?;
 
     let note_amount = amount.as_deposit_notes(reserve_info, Rounding::Up)?;
 
@@ -92,7 +92,7 @@ pub fn handler(ctx: Context<WithdrawTokens>, amount: Amount) -> ProgramResult {
     let clock = Clock::get().unwrap();
     let reserve_info = market.reserves().get_cached(reserve.index, clock.slot);
 
-    market.verify_ability_deposit_withdraw()?;
+    market.verify_ability_deposit_withdraw(&reserve)?;
 
     // Calculate the number of tokens that the request amount is worth
     let token_amount = amount.as_tokens(reserve_info, Rounding::Down);
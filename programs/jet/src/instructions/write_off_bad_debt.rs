@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Copyright (C) 2021 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use anchor_lang::prelude::*;
+use anchor_lang::Key;
+use anchor_spl::token::{self, Burn};
+
+use crate::errors::ErrorCode;
+use crate::state::*;
+
+#[event]
+pub struct BadDebtWrittenOff {
+    obligation: Pubkey,
+    reserve: Pubkey,
+    loan_notes: u64,
+    debt: u64,
+    covered_by_insurance: u64,
+}
+
+#[derive(Accounts)]
+pub struct WriteOffBadDebt<'info> {
+    /// The relevant market the debt is in
+    #[account(has_one = owner,
+              has_one = market_authority)]
+    pub market: Loader<'info, Market>,
+
+    /// The market's authority account
+    pub market_authority: AccountInfo<'info>,
+
+    /// The obligation with debt to be written off
+    #[account(mut,
+              has_one = market,
+              constraint = obligation.load().unwrap().has_loan_custody(&loan_account.key()))]
+    pub obligation: Loader<'info, Obligation>,
+
+    /// The reserve that the debt is from
+    #[account(mut,
+              has_one = market,
+              has_one = loan_note_mint)]
+    pub reserve: Loader<'info, Reserve>,
+
+    /// The mint for the debt/loan notes
+    #[account(mut)]
+    pub loan_note_mint: AccountInfo<'info>,
+
+    /// The account that holds the borrower's debt balance
+    #[account(mut)]
+    pub loan_account: AccountInfo<'info>,
+
+    /// The owner of the market
+    #[account(signer)]
+    pub owner: AccountInfo<'info>,
+
+    #[account(address = token::ID)]
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> WriteOffBadDebt<'info> {
+    fn note_burn_context(&self) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
+        CpiContext::new(
+            self.token_program.clone(),
+            Burn {
+                to: self.loan_account.clone(),
+                mint: self.loan_note_mint.clone(),
+                authority: self.market_authority.clone(),
+            },
+        )
+    }
+}
+
+/// Remove the remaining debt of an obligation with no collateral left, covering
+/// it from the reserve's insurance fund and then from its depositors.
+pub fn handler(ctx: Context<WriteOffBadDebt>) -> ProgramResult {
+    let clock = Clock::get()?;
+    let market = ctx.accounts.market.load()?;
+    let mut reserve = ctx.accounts.reserve.load_mut()?;
+    reserve.verify_unlocked()?;
+
+    let mut obligation = ctx.accounts.obligation.load_mut()?;
+    let loan_account = &ctx.accounts.loan_account;
+
+    if obligation.has_collateral() {
+        return Err(ErrorCode::ObligationHasCollateral.into());
+    }
+
+    let loan_notes = token::accessor::amount(loan_account)?;
+
+    token::burn(
+        ctx.accounts
+            .note_burn_context()
+            .with_signer(&[&market.authority_seeds()]),
+        loan_notes,
+    )?;
+
+    let (debt, covered) = reserve.write_off_debt(clock.slot, loan_notes)?;
+
+    obligation.repay(&loan_account.key(), reserve.amount(loan_notes))?;
+
+    emit!(BadDebtWrittenOff {
+        obligation: ctx.accounts.obligation.key(),
+        reserve: ctx.accounts.reserve.key(),
+        loan_notes,
+        debt: debt.as_u64(0),
+        covered_by_insurance: covered.as_u64(0),
+    });
+
+    Ok(())
+}
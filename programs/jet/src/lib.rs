@@ -58,7 +58,7 @@ mod jet {
         instructions::init_reserve::handler(ctx, bump, config)
     }
 
-    /// Replace an existing reserve config
+    /// Replace an existing reserve config, subject to the market's config delay
     pub fn update_reserve_config(
         ctx: Context<UpdateReserveConfig>,
         new_config: ReserveConfig,
@@ -66,6 +66,29 @@ mod jet {
         instructions::update_reserve_config::handler(ctx, new_config)
     }
 
+    /// Change the risk parameters of a reserve's config, as the market's risk admin
+    pub fn update_reserve_risk_config(
+        ctx: Context<UpdateReserveRiskConfig>,
+        new_config: ReserveConfig,
+    ) -> ProgramResult {
+        instructions::update_reserve_risk_config::handler(ctx, new_config)
+    }
+
+    /// Apply a queued change to a reserve's config, once the market's delay has passed
+    pub fn apply_reserve_config(ctx: Context<ApplyReserveConfig>) -> ProgramResult {
+        instructions::apply_reserve_config::handler(ctx)
+    }
+
+    /// Cancel a queued change to a reserve's config
+    pub fn cancel_reserve_config(ctx: Context<CancelReserveConfig>) -> ProgramResult {
+        instructions::cancel_reserve_config::handler(ctx)
+    }
+
+    /// Remove a deprecated reserve from its market, once it has no deposits or loans
+    pub fn remove_reserve(ctx: Context<RemoveReserve>) -> ProgramResult {
+        instructions::remove_reserve::handler(ctx)
+    }
+
     /// Initialize an account that can be used to store deposit notes
     pub fn init_deposit_account(ctx: Context<InitializeDepositAccount>, bump: u8) -> ProgramResult {
         instructions::init_deposit_account::handler(ctx, bump)
@@ -89,9 +112,22 @@ mod jet {
         instructions::init_obligation::handler(ctx, bump)
     }
 
-    /// Change the owner on a market
-    pub fn set_market_owner(ctx: Context<SetMarketOwner>, new_owner: Pubkey) -> ProgramResult {
-        instructions::set_market_owner::handler(ctx, new_owner)
+    /// Propose a new owner for a market, which must accept before it takes effect
+    pub fn propose_market_owner(
+        ctx: Context<ProposeMarketOwner>,
+        new_owner: Pubkey,
+    ) -> ProgramResult {
+        instructions::propose_market_owner::handler(ctx, new_owner)
+    }
+
+    /// Accept the ownership of a market, as its proposed owner
+    pub fn accept_market_owner(ctx: Context<AcceptMarketOwner>) -> ProgramResult {
+        instructions::accept_market_owner::handler(ctx)
+    }
+
+    /// Cancel a proposed change of a market's owner
+    pub fn cancel_market_owner(ctx: Context<CancelMarketOwner>) -> ProgramResult {
+        instructions::cancel_market_owner::handler(ctx)
     }
 
     /// Change the flags on a market
@@ -99,6 +135,104 @@ mod jet {
         instructions::set_market_flags::handler(ctx, flags)
     }
 
+    /// Assign the pause guardian and risk admin for a market, along with how
+    /// far the risk admin may move a reserve's risk parameters in one change
+    pub fn set_market_roles(
+        ctx: Context<SetMarketRoles>,
+        pause_guardian: Pubkey,
+        risk_admin: Pubkey,
+        risk_admin_max_change: u16,
+    ) -> ProgramResult {
+        instructions::set_market_roles::handler(
+            ctx,
+            pause_guardian,
+            risk_admin,
+            risk_admin_max_change,
+        )
+    }
+
+    /// Assign the account which receives the fees collected by a market
+    pub fn set_market_treasury(
+        ctx: Context<SetMarketTreasury>,
+        fee_treasury: Pubkey,
+    ) -> ProgramResult {
+        instructions::set_market_treasury::handler(ctx, fee_treasury)
+    }
+
+    /// Halt operations on a market, as the market's pause guardian
+    pub fn pause_market(ctx: Context<PauseMarket>, flags: u64) -> ProgramResult {
+        instructions::pause_market::handler(ctx, flags)
+    }
+
+    /// Halt operations on a reserve, as the market's pause guardian
+    pub fn pause_reserve(ctx: Context<PauseReserve>, flags: u64) -> ProgramResult {
+        instructions::pause_reserve::handler(ctx, flags)
+    }
+
+    /// Define the risk parameters for an asset category in a market
+    pub fn set_asset_category(
+        ctx: Context<SetAssetCategory>,
+        category: u16,
+        params: AssetCategory,
+    ) -> ProgramResult {
+        instructions::set_asset_category::handler(ctx, category, params)
+    }
+
+    /// Apply a queued change to an asset category, once the market's delay has passed
+    pub fn apply_asset_category(ctx: Context<ApplyAssetCategory>) -> ProgramResult {
+        instructions::apply_asset_category::handler(ctx)
+    }
+
+    /// Assign a reserve to an asset category
+    pub fn set_reserve_category(ctx: Context<SetReserveCategory>, category: u16) -> ProgramResult {
+        instructions::set_reserve_category::handler(ctx, category)
+    }
+
+    /// Apply a queued change to a reserve's asset category, once the market's delay has passed
+    pub fn apply_reserve_category(ctx: Context<ApplyReserveCategory>) -> ProgramResult {
+        instructions::apply_reserve_category::handler(ctx)
+    }
+
+    /// Change the delay required before reserve config changes take effect
+    ///
+    /// A shorter delay only takes effect once the current delay has passed.
+    pub fn set_reserve_config_delay(
+        ctx: Context<SetReserveConfigDelay>,
+        delay: i64,
+    ) -> ProgramResult {
+        instructions::set_reserve_config_delay::handler(ctx, delay)
+    }
+
+    /// Change the flags on a reserve
+    pub fn set_reserve_flags(ctx: Context<SetReserveFlags>, flags: u64) -> ProgramResult {
+        instructions::set_reserve_flags::handler(ctx, flags)
+    }
+
+    /// Change the oracle accounts used to price a reserve's token
+    pub fn update_reserve_oracle(ctx: Context<UpdateReserveOracle>) -> ProgramResult {
+        instructions::update_reserve_oracle::handler(ctx)
+    }
+
+    /// Move a reserve to a new DEX market for liquidating its token
+    pub fn update_reserve_dex_market(
+        ctx: Context<UpdateReserveDexMarket>,
+        bump: u8,
+    ) -> ProgramResult {
+        instructions::update_reserve_dex_market::handler(ctx, bump)
+    }
+
+    /// Register an additional price source for a reserve
+    pub fn set_reserve_secondary_oracle(ctx: Context<SetReserveSecondaryOracle>) -> ProgramResult {
+        instructions::set_reserve_secondary_oracle::handler(ctx)
+    }
+
+    /// Stop using the additional price source for a reserve
+    pub fn clear_reserve_secondary_oracle(
+        ctx: Context<ClearReserveSecondaryOracle>,
+    ) -> ProgramResult {
+        instructions::clear_reserve_secondary_oracle::handler(ctx)
+    }
+
     /// Close a deposit account
     pub fn close_deposit_account(ctx: Context<CloseDepositAccount>, bump: u8) -> ProgramResult {
         instructions::close_deposit_account::handler(ctx, bump)
@@ -170,6 +304,15 @@ mod jet {
         instructions::repay::handler(ctx, amount)
     }
 
+    /// Borrow tokens from a reserve, to be repaid with fees within the same instruction
+    pub fn flash_loan<'info>(
+        ctx: Context<'_, '_, '_, 'info, FlashLoan<'info>>,
+        amount: u64,
+        data: Vec<u8>,
+    ) -> ProgramResult {
+        instructions::flash_loan::handler(ctx, amount, data)
+    }
+
     /// Liquidate an unhealthy loan
     pub fn liquidate(
         ctx: Context<Liquidate>,
@@ -184,11 +327,28 @@ mod jet {
         panic!("not supported")
     }
 
+    /// Move the fees collected by a reserve to the market's treasury
+    pub fn collect_fees(ctx: Context<CollectFees>) -> ProgramResult {
+        instructions::collect_fees::handler(ctx)
+    }
+
+    /// Write off the debt left in an obligation after all its collateral is gone
+    pub fn write_off_bad_debt(ctx: Context<WriteOffBadDebt>) -> ProgramResult {
+        instructions::write_off_bad_debt::handler(ctx)
+    }
+
+    /// Move tokens from a reserve's insurance fund to the market's treasury
+    pub fn release_insurance_fund(
+        ctx: Context<ReleaseInsuranceFund>,
+        amount: u64,
+    ) -> ProgramResult {
+        instructions::release_insurance_fund::handler(ctx, amount)
+    }
+
     /// Refresh a reserve's market price and interest owed
     ///
-    /// If the reserve is extremely stale, only a partial update will be
-    /// performed. It may be necessary to call refresh_reserve multiple
-    /// times to get the reserve up to date.
+    /// When the reserve has a secondary oracle, its price account must be
+    /// provided as the first remaining account.
     pub fn refresh_reserve(ctx: Context<RefreshReserve>) -> ProgramResult {
         instructions::refresh_reserve::handler(ctx)
     }
@@ -18,6 +18,7 @@
 use std::ops::{Deref, DerefMut};
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::clock::UnixTimestamp;
 use bytemuck::{Pod, Zeroable};
 use pyth_client::Product;
 
@@ -28,7 +29,11 @@ use crate::errors::ErrorCode;
 use crate::utils::{FixedBuf, StoredPubkey};
 use crate::Rounding;
 
-use super::Cache;
+use super::{Cache, Reserve, ReserveFlags};
+
+/// The number of asset categories that can be defined in a market, including
+/// the unused category zero.
+pub const MAX_ASSET_CATEGORIES: usize = 16;
 
 /// Lending market account
 #[assert_size(12800)]
@@ -62,8 +67,47 @@ pub struct Market {
     /// Storage for flags that can be set on the market.
     pub flags: u64,
 
+    /// The number of seconds that must pass before a change to a
+    /// reserve's config can be applied.
+    reserve_config_delay: i64,
+
+    /// The account proposed to become the new owner of the market, which
+    /// must accept the ownership before it's transferred.
+    pub pending_owner: Pubkey,
+
+    /// The account allowed to halt operations in the market, without
+    /// having any other authority over it.
+    pub pause_guardian: Pubkey,
+
+    /// The account allowed to adjust the risk parameters of reserves
+    /// in the market.
+    pub risk_admin: Pubkey,
+
+    /// The parameters for the asset categories defined in the market. The
+    /// first entry is unused, since category zero means no category.
+    asset_categories: [AssetCategory; MAX_ASSET_CATEGORIES],
+
+    /// The account which must own the token accounts receiving fees
+    /// collected from the market's reserves.
+    pub fee_treasury: Pubkey,
+
+    /// A shorter delay for reserve config changes, which replaces the
+    /// current one once the current delay has passed.
+    pending_reserve_config_delay: i64,
+    pending_reserve_config_delay_activation: i64,
+
+    /// The most the risk admin may move a reserve's risk parameter in a
+    /// single change, in basis points of the parameter's current value.
+    pub risk_admin_max_change: u16,
+
+    /// A change to an asset category that has been queued, but not yet applied.
+    /// The activation time is zero when there is no pending change.
+    pending_asset_category: AssetCategory,
+    pending_asset_category_index: u16,
+    pending_asset_category_activation: i64,
+
     /// Unused space before start of reserve list
-    _reserved: [u8; 352],
+    _reserved: [u8; 52],
 
     /// The storage for information on reserves in the market
     reserves: [u8; 12288],
@@ -132,33 +176,239 @@ impl Market {
         self.flags = flags.bits();
     }
 
-    /// Verify that the market is currently allowing deposits and withdrawals
-    pub fn verify_ability_deposit_withdraw(&self) -> Result<(), ErrorCode> {
+    /// Get the delay required before reserve config changes can be applied
+    pub fn reserve_config_delay(&self, current_time: UnixTimestamp) -> i64 {
+        let activation = self.pending_reserve_config_delay_activation;
+
+        match activation != 0 && current_time >= activation {
+            true => self.pending_reserve_config_delay,
+            false => self.reserve_config_delay,
+        }
+    }
+
+    /// Change the delay required before reserve config changes can be applied.
+    ///
+    /// A longer delay takes effect immediately, while a shorter one only takes
+    /// effect once the current delay has passed, so that it can't be used to
+    /// skip the delay for other changes. Returns the time the new delay takes
+    /// effect, if it's been queued.
+    pub fn set_reserve_config_delay(
+        &mut self,
+        delay: i64,
+        current_time: UnixTimestamp,
+    ) -> Result<Option<UnixTimestamp>, ErrorCode> {
+        if delay < 0 {
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        let current_delay = self.reserve_config_delay(current_time);
+
+        self.reserve_config_delay = current_delay;
+        self.pending_reserve_config_delay = 0;
+        self.pending_reserve_config_delay_activation = 0;
+
+        if delay >= current_delay {
+            self.reserve_config_delay = delay;
+            return Ok(None);
+        }
+
+        let activation = current_time
+            .checked_add(current_delay)
+            .ok_or(ErrorCode::ArithmeticError)?;
+
+        self.pending_reserve_config_delay = delay;
+        self.pending_reserve_config_delay_activation = activation;
+
+        Ok(Some(activation))
+    }
+
+    /// Get the parameters for an asset category, if it has been defined
+    pub fn asset_category(&self, category: u16) -> Option<AssetCategory> {
+        let categories = self.asset_categories;
+
+        match categories.get(category as usize) {
+            Some(params) if category != 0 && params.is_defined() => Some(*params),
+            _ => None,
+        }
+    }
+
+    /// Define the parameters for an asset category
+    pub fn set_asset_category(
+        &mut self,
+        category: u16,
+        params: AssetCategory,
+    ) -> Result<(), ErrorCode> {
+        verify_asset_category_index(category)?;
+
+        let mut categories = self.asset_categories;
+        categories[category as usize] = params;
+        self.asset_categories = categories;
+
+        Ok(())
+    }
+
+    /// Queue a change to the parameters for an asset category, replacing any
+    /// change already pending.
+    pub fn queue_asset_category(
+        &mut self,
+        category: u16,
+        params: AssetCategory,
+        activation: UnixTimestamp,
+    ) -> Result<(), ErrorCode> {
+        verify_asset_category_index(category)?;
+
+        self.pending_asset_category = params;
+        self.pending_asset_category_index = category;
+        self.pending_asset_category_activation = activation;
+
+        Ok(())
+    }
+
+    /// Apply the pending change to an asset category, if it's ready
+    pub fn apply_pending_asset_category(
+        &mut self,
+        current_time: UnixTimestamp,
+    ) -> Result<(), ErrorCode> {
+        let activation = self.pending_asset_category_activation;
+
+        if activation == 0 {
+            return Err(ErrorCode::NoPendingAssetCategory);
+        }
+
+        if current_time < activation {
+            msg!("pending asset category can be applied after {}", activation);
+            return Err(ErrorCode::PendingAssetCategoryNotReady);
+        }
+
+        self.set_asset_category(
+            self.pending_asset_category_index,
+            self.pending_asset_category,
+        )?;
+        self.cancel_pending_asset_category();
+
+        Ok(())
+    }
+
+    /// Discard the pending change to an asset category
+    pub fn cancel_pending_asset_category(&mut self) {
+        self.pending_asset_category = AssetCategory::zeroed();
+        self.pending_asset_category_index = 0;
+        self.pending_asset_category_activation = 0;
+    }
+
+    /// Verify that the market and reserve are currently allowing deposits and withdrawals
+    pub fn verify_ability_deposit_withdraw(&self, reserve: &Reserve) -> Result<(), ErrorCode> {
+        reserve.verify_unlocked()?;
+
         if self.flags().contains(MarketFlags::HALT_DEPOSITS) {
             msg!("the market is currently not allowing deposits/withdrawals");
             return Err(ErrorCode::MarketHalted);
         }
 
+        if reserve.flags().contains(ReserveFlags::HALT_DEPOSITS) {
+            msg!("the reserve is currently not allowing deposits/withdrawals");
+            return Err(ErrorCode::ReserveHalted);
+        }
+
         Ok(())
     }
 
-    /// Verify that the market is currently allowing changes to borrows
-    pub fn verify_ability_borrow(&self) -> Result<(), ErrorCode> {
+    /// Verify that the market and reserve are currently allowing changes to borrows
+    pub fn verify_ability_borrow(&self, reserve: &Reserve) -> Result<(), ErrorCode> {
+        reserve.verify_unlocked()?;
+
         if self.flags().contains(MarketFlags::HALT_BORROWS) {
             msg!("the market is currently not allowing borrows");
             return Err(ErrorCode::MarketHalted);
         }
 
+        if reserve.flags().contains(ReserveFlags::HALT_BORROWS) {
+            msg!("the reserve is currently not allowing borrows");
+            return Err(ErrorCode::ReserveHalted);
+        }
+
         Ok(())
     }
 
-    /// Verify that the market is currently allowing repayments to loans
-    pub fn verify_ability_repay(&self) -> Result<(), ErrorCode> {
+    /// Verify that the market and reserve are currently allowing repayments to loans
+    pub fn verify_ability_repay(&self, reserve: &Reserve) -> Result<(), ErrorCode> {
+        reserve.verify_unlocked()?;
+
         if self.flags().contains(MarketFlags::HALT_REPAYS) {
-            msg!("the market is currently not allowing borrows");
+            msg!("the market is currently not allowing repays");
             return Err(ErrorCode::MarketHalted);
         }
 
+        if reserve.flags().contains(ReserveFlags::HALT_REPAYS) {
+            msg!("the reserve is currently not allowing repays");
+            return Err(ErrorCode::ReserveHalted);
+        }
+
+        Ok(())
+    }
+}
+
+fn verify_asset_category_index(category: u16) -> Result<(), ErrorCode> {
+    if category == 0 || category as usize >= MAX_ASSET_CATEGORIES {
+        msg!(
+            "asset category must be between 1 and {}",
+            MAX_ASSET_CATEGORIES - 1
+        );
+        return Err(ErrorCode::InvalidParameter);
+    }
+
+    Ok(())
+}
+
+/// Risk parameters shared by a group of similar assets, applied to obligations
+/// where all loans and collateral are in the same category.
+#[assert_size(8)]
+#[derive(Pod, Zeroable, Clone, Copy, Debug, AnchorDeserialize, AnchorSerialize)]
+#[repr(C)]
+pub struct AssetCategory {
+    /// The minimum allowable collateralization ratio for loans within the category
+    pub min_collateral_ratio: u16,
+
+    /// The collateralization ratio below which loans within the category may
+    /// be liquidated. When zero the `min_collateral_ratio` is used instead.
+    pub liquidation_collateral_ratio: u16,
+
+    /// The bonus awarded to liquidators of loans within the category
+    pub liquidation_premium: u16,
+
+    pub _reserved: u16,
+}
+
+impl AssetCategory {
+    /// Check if the category has been given any parameters
+    pub fn is_defined(&self) -> bool {
+        self.min_collateral_ratio != 0
+    }
+
+    /// The collateralization ratio below which loans within the category can be liquidated
+    pub fn liquidation_collateral_ratio(&self) -> u16 {
+        match self.liquidation_collateral_ratio {
+            0 => self.min_collateral_ratio,
+            ratio => ratio,
+        }
+    }
+
+    /// Check that the parameters are consistent with each other
+    pub fn validate(&self) -> Result<(), ErrorCode> {
+        let min_ratio = 10_000 + self.liquidation_premium as u32;
+
+        if (self.min_collateral_ratio as u32) <= min_ratio {
+            msg!("min_collateral_ratio must be above 100% plus the liquidation_premium");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        if self.liquidation_collateral_ratio() > self.min_collateral_ratio
+            || (self.liquidation_collateral_ratio() as u32) <= min_ratio
+        {
+            msg!("liquidation_collateral_ratio must be between 100% plus the liquidation_premium and the min_collateral_ratio");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
         Ok(())
     }
 }
@@ -191,6 +441,21 @@ impl MarketReserves {
         self.reserve_info[index as usize] = ReserveInfo::zeroed();
     }
 
+    /// Verify that the entry at the index still belongs to the given reserve,
+    /// since the entries of removed reserves are reused.
+    pub fn verify_registered(
+        &self,
+        index: ReserveIndex,
+        reserve: &Pubkey,
+    ) -> Result<(), ErrorCode> {
+        if *self.get(index).reserve != *reserve {
+            msg!("reserve {} is not registered at #{}", reserve, index);
+            return Err(ErrorCode::ReserveNotRegistered);
+        }
+
+        Ok(())
+    }
+
     pub fn get_mut(&mut self, index: ReserveIndex) -> &mut ReserveInfo {
         &mut self.reserve_info[index as usize]
     }
@@ -228,13 +493,13 @@ impl MarketReserves {
     pub fn iter(&self) -> impl Iterator<Item = &ReserveInfo> {
         self.reserve_info
             .iter()
-            .take_while(|r| r.reserve != Pubkey::default())
+            .filter(|r| r.reserve != Pubkey::default())
     }
 
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut ReserveInfo> {
         self.reserve_info
             .iter_mut()
-            .take_while(|r| r.reserve != Pubkey::default())
+            .filter(|r| r.reserve != Pubkey::default())
     }
 }
 
@@ -273,11 +538,66 @@ pub struct CachedReserveInfo {
     /// collateral asset.
     pub liquidation_bonus: u16,
 
+    /// The bonus awarded to liquidators for loans within the reserve's asset category
+    pub category_liquidation_bonus: u16,
+
+    /// The asset category the reserve belongs to, or zero for none
+    pub category: u16,
+
+    _reserved0: [u8; 2],
+
+    /// The collateralization ratio below which a loan on this reserve may be liquidated
+    pub liquidation_collateral_ratio: Number,
+
+    /// The portion of the value of this reserve's tokens counted as collateral
+    pub collateral_factor: Number,
+
+    /// The minimum collateralization ratio for loans within the reserve's asset category
+    pub category_min_collateral_ratio: Number,
+
+    /// The liquidation collateralization ratio for loans within the reserve's asset category
+    pub category_liquidation_collateral_ratio: Number,
+
+    /// The conservative price used for valuing the asset as collateral.
+    /// USD per smallest unit (1u64) of a token
+    pub collateral_price: Number,
+
+    /// The conservative price used for valuing the asset as debt.
+    /// USD per smallest unit (1u64) of a token
+    pub debt_price: Number,
+
     /// Unused space
-    _reserved: FixedBuf<158>,
+    _reserved: FixedBuf<8>,
 }
 
 impl CachedReserveInfo {
+    /// The minimum collateralization ratio, depending on whether an obligation
+    /// is limited to the reserve's asset category
+    pub fn effective_min_collateral_ratio(&self, in_category: bool) -> Number {
+        match in_category {
+            true => self.category_min_collateral_ratio,
+            false => self.min_collateral_ratio,
+        }
+    }
+
+    /// The liquidation collateralization ratio, depending on whether an obligation
+    /// is limited to the reserve's asset category
+    pub fn effective_liquidation_collateral_ratio(&self, in_category: bool) -> Number {
+        match in_category {
+            true => self.category_liquidation_collateral_ratio,
+            false => self.liquidation_collateral_ratio,
+        }
+    }
+
+    /// The liquidation bonus, depending on whether an obligation is limited
+    /// to the reserve's asset category
+    pub fn effective_liquidation_bonus(&self, in_category: bool) -> u16 {
+        match in_category {
+            true => self.category_liquidation_bonus,
+            false => self.liquidation_bonus,
+        }
+    }
+
     /// USD per smallest unit (1u64) of the deposit note
     pub fn deposit_note_price(&self) -> Number {
         self.deposit_note_exchange_rate * self.price
@@ -40,6 +40,33 @@ const MAX_OBLIGATION_POSITIONS: usize = 11;
 /// the minimum collateralization ratio.
 const MIN_PARTIAL_LIQUIDATION_VALUE: u64 = 10;
 
+/// Get the value of collateral which, if sold to a liquidator at the given bonus,
+/// would bring an obligation back to the minimum collateral ratio.
+///
+/// Selling collateral worth `x` repays `x / (1 + bonus)` of the loan, while reducing the
+/// weighted collateral by `x * collateral_factor`, so the value that restores the minimum
+/// ratio is where `c_weighted - x * factor = min_c_ratio * (loan - x / (1 + bonus))`.
+///
+/// Returns `None` if no sale can restore the ratio, which is the case when the collateral
+/// factor is at least `min_c_ratio / (1 + bonus)`, since each sale then removes as much
+/// weighted collateral as it frees up from the requirement on the loan.
+pub fn restoring_sell_value(
+    min_c_ratio: Number,
+    loan_value: Number,
+    collateral_weighted: Number,
+    liquidation_bonus: Number,
+    collateral_factor: Number,
+) -> Option<Number> {
+    let discounted_ratio = min_c_ratio / (Number::ONE + liquidation_bonus);
+
+    if discounted_ratio <= collateral_factor {
+        return None;
+    }
+
+    let shortfall = (min_c_ratio * loan_value).saturating_sub(collateral_weighted);
+    Some(shortfall / (discounted_ratio - collateral_factor))
+}
+
 #[assert_size(4608)]
 /// Tracks information about a user's obligation to repay a borrowed position.
 #[account(zero_copy)]
@@ -150,23 +177,64 @@ impl Obligation {
         let values = cached.get_stale_mut();
         values.loan_value = loans._market_value(market, current_slot);
         values.collateral_value = collateral._market_value(market, current_slot);
+        values.weighted_collateral_value = collateral._weighted_value(market, current_slot);
     }
 
-    /// Determine if the obligation is healthy, or otherwise unhealthy and
-    /// at risk of liquidation.
+    /// Determine if the obligation is healthy, meaning it is collateralized
+    /// enough to allow borrowing more or withdrawing collateral.
     pub fn is_healthy(&self, market: &MarketReserves, current_slot: u64) -> bool {
-        let max_min_c_ratio: Number;
-        let _max_min_c_ratio = self
+        self.meets_collateral_ratio(market, current_slot, |r, in_category| {
+            r.effective_min_collateral_ratio(in_category)
+        })
+    }
+
+    /// Determine if the obligation is collateralized poorly enough that it
+    /// can be liquidated.
+    pub fn is_liquidatable(&self, market: &MarketReserves, current_slot: u64) -> bool {
+        !self.meets_collateral_ratio(market, current_slot, |r, in_category| {
+            r.effective_liquidation_collateral_ratio(in_category)
+        })
+    }
+
+    /// Determine if all the loans and collateral in this obligation belong to
+    /// the same asset category, which allows the category's risk parameters
+    /// to be used instead of the reserves' own.
+    pub fn in_category(&self, market: &MarketReserves, current_slot: u64) -> bool {
+        let mut categories = self
             .loans()
-            .iter()
+            .held()
+            .chain(self.collateral().held())
+            .map(|p| market.get_cached(p.reserve_index, current_slot).category);
+
+        match categories.next() {
+            Some(first) => first != 0 && categories.all(|c| c == first),
+            None => false,
+        }
+    }
+
+    /// Check the collateral value, weighted by each collateral's factor,
+    /// against the loan value, using the largest of the ratios required by
+    /// the reserves being borrowed from.
+    fn meets_collateral_ratio(
+        &self,
+        market: &MarketReserves,
+        current_slot: u64,
+        required_ratio: impl Fn(&CachedReserveInfo, bool) -> Number,
+    ) -> bool {
+        let in_category = self.in_category(market, current_slot);
+        let max_c_ratio: Number;
+        let _max_c_ratio = self
+            .loans()
+            .held()
             .map(|p| {
-                market
-                    .get_cached(p.reserve_index, current_slot)
-                    .min_collateral_ratio
+                required_ratio(
+                    market.get_cached(p.reserve_index, current_slot),
+                    in_category,
+                )
             })
             .max();
-        if let Some(c) = _max_min_c_ratio {
-            max_min_c_ratio = c;
+        if let Some(c) = _max_c_ratio {
+            max_c_ratio = c;
         } else {
             return true; // No loans
         }
@@ -174,9 +242,9 @@ impl Obligation {
         let cached: &CalculationCache = bytemuck::from_bytes(&self.cached);
 
         let cache_values = cached.expect(current_slot, "calculations not performed");
-        let min_collateral_value = cache_values.loan_value * max_min_c_ratio;
+        let min_collateral_value = cache_values.loan_value * max_c_ratio;
 
-        min_collateral_value <= cache_values.collateral_value
+        min_collateral_value <= cache_values.weighted_collateral_value
     }
 
     /// Liquidate a loan on this obligation
@@ -191,44 +259,52 @@ impl Obligation {
         loan_account: &Pubkey,
         repay_notes_amount: Number,
     ) -> Result<Number, ErrorCode> {
+        let in_category = self.in_category(market, current_slot);
         let loan_total = self.loan_value(market, current_slot);
         let loan = self.loans().position(loan_account)?;
         let loan_reserve = market.get_cached(loan.reserve_index, current_slot);
 
         let collateral_total = self.collateral_value(market, current_slot);
+        let collateral_weighted = self.weighted_collateral_value(market, current_slot);
         let collateral = self.collateral_mut().position(collateral_account)?;
         let collateral_reserve = market.get_cached(collateral.reserve_index, current_slot);
 
-        // calculate the value of the debt being repaid
+        // calculate the value of the debt being repaid, where the exchange between
+        // debt and collateral happens at the oracle prices
         let repaid_value =
             repay_notes_amount * loan_reserve.loan_note_exchange_rate * loan_reserve.price;
         let repaid_ratio = repaid_value / loan_total;
 
         // Adjust the repaid value based on the configured bonus for liquidators
-        let min_c_ratio = loan_reserve.min_collateral_ratio;
-        let liquidation_bonus = Number::from_bps(collateral_reserve.liquidation_bonus);
+        let min_c_ratio = loan_reserve.effective_min_collateral_ratio(in_category);
+        let liquidation_c_ratio = loan_reserve.effective_liquidation_collateral_ratio(in_category);
+        let liquidation_bonus =
+            Number::from_bps(collateral_reserve.effective_liquidation_bonus(in_category));
 
         // Limit collateral withdrawl based on the sellable value which, if sold,
         // would bring the obligation back to a healthy position.
-        let loan_to_value = loan_total / collateral_total;
-        let c_ratio_ltv = min_c_ratio * loan_to_value;
-
-        let collateral_max_value = if c_ratio_ltv < Number::ONE {
+        let collateral_max_value = if liquidation_c_ratio * loan_total < collateral_weighted {
             // This means the loan is over-collateralized, so we shouldn't allow
             // any liquidation for it.
-            msg!("c_ratio_ltv < 1 implies this cannot be liquidated");
+            msg!("collateral value is above the liquidation ratio, so this cannot be liquidated");
             return Err(ErrorCode::ObligationHealthy);
         } else {
             collateral_total * repaid_ratio
         };
 
-        let limit_fraction = (c_ratio_ltv - Number::ONE)
-            / (min_c_ratio / (Number::ONE + liquidation_bonus) - Number::ONE);
-
-        let collateral_sellable_value = std::cmp::min(
-            (Number::ONE + liquidation_bonus) * repaid_value,
-            limit_fraction * collateral_total,
-        );
+        // When no sale can restore the minimum ratio, the liquidator is only limited
+        // by the amount repaid.
+        let repaid_bonus_value = (Number::ONE + liquidation_bonus) * repaid_value;
+        let collateral_sellable_value = match restoring_sell_value(
+            min_c_ratio,
+            loan_total,
+            collateral_weighted,
+            liquidation_bonus,
+            collateral_reserve.collateral_factor,
+        ) {
+            Some(restoring_value) => std::cmp::min(repaid_bonus_value, restoring_value),
+            None => repaid_bonus_value,
+        };
 
         // Set a minimum sellable amount, so that positions worth less than the minimum
         // can always be fully liquidated.
@@ -276,11 +352,7 @@ impl Obligation {
     ) -> bool {
         self.collateral().iter().any(|p| {
             p.account.as_ref() == collateral
-                && market
-                    .reserves()
-                    .iter()
-                    .enumerate()
-                    .any(|(index, r)| *r.reserve == *reserve && (index as u16) == p.reserve_index)
+                && *market.reserves().get(p.reserve_index).reserve == *reserve
         })
     }
 
@@ -292,6 +364,14 @@ impl Obligation {
         self.collateral()._market_value(market, current_slot)
     }
 
+    pub fn weighted_collateral_value(&self, market: &MarketReserves, current_slot: u64) -> Number {
+        if let Ok(values) = self.cached().try_get(current_slot) {
+            return values.weighted_collateral_value;
+        }
+
+        self.collateral()._weighted_value(market, current_slot)
+    }
+
     pub fn loan_value(&self, market: &MarketReserves, current_slot: u64) -> Number {
         if let Ok(values) = self.cached().try_get(current_slot) {
             return values.loan_value;
@@ -300,6 +380,11 @@ impl Obligation {
         self.loans()._market_value(market, current_slot)
     }
 
+    /// Determine if any collateral remains in the obligation
+    pub fn has_collateral(&self) -> bool {
+        self.collateral().iter().any(|p| p.amount != Number::ZERO)
+    }
+
     pub fn position_count(&self) -> usize {
         let collaterals = self.collateral().iter().count();
         let loans = self.loans().iter().count();
@@ -338,8 +423,9 @@ impl Obligation {
 struct CalculationCacheInner {
     collateral_value: Number,
     loan_value: Number,
+    weighted_collateral_value: Number,
 
-    _reserved: FixedBuf<192>,
+    _reserved: FixedBuf<168>,
 }
 
 type CalculationCache = Cache<CalculationCacheInner, 0>;
@@ -443,7 +529,7 @@ impl ObligationSide {
     pub fn market_value(&self, market_info: &MarketReserves, current_slot: u64) -> PositionValue {
         let mut value = PositionValue::zeroed();
 
-        for position in self.iter() {
+        for position in self.held() {
             let reserve = market_info.get(position.reserve_index).unwrap(current_slot);
             let position_value = position.market_value(reserve);
             value.market_value += position_value.market_value;
@@ -456,7 +542,7 @@ impl ObligationSide {
     fn _market_value(&self, market: &MarketReserves, current_slot: u64) -> Number {
         let mut value = Number::ZERO;
 
-        for pos in self.iter() {
+        for pos in self.held() {
             let reserve = market.get_cached(pos.reserve_index, current_slot);
             value += pos._market_value(reserve);
         }
@@ -464,11 +550,29 @@ impl ObligationSide {
         value
     }
 
+    fn _weighted_value(&self, market: &MarketReserves, current_slot: u64) -> Number {
+        let mut value = Number::ZERO;
+
+        for pos in self.held() {
+            let reserve = market.get_cached(pos.reserve_index, current_slot);
+            value += pos._market_value(reserve) * reserve.collateral_factor;
+        }
+
+        value
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &Position> {
         self.positions
             .iter()
             .filter(|p| p.account != Pubkey::default())
     }
+
+    /// The positions holding any notes. Empty positions are skipped, since
+    /// their reserve may have been removed from the market, leaving its entry
+    /// stale or reused by another reserve.
+    pub fn held(&self) -> impl Iterator<Item = &Position> {
+        self.iter().filter(|p| p.amount != Number::ZERO)
+    }
 }
 
 /// Information about a single collateral or loan account registered with an obligation
@@ -522,12 +626,24 @@ impl Position {
     }
 
     fn _market_value(&self, reserve: &CachedReserveInfo) -> Number {
-        self.amount * self.note_exchange_rate(reserve) * reserve.price
+        self.amount * self.note_exchange_rate(reserve) * self.price(reserve)
+    }
+
+    fn price(&self, reserve: &CachedReserveInfo) -> Number {
+        match Side::from_integer(self.side).expect("invalid side value") {
+            Side::Collateral => reserve.collateral_price,
+            Side::Loan => reserve.debt_price,
+        }
     }
 
     fn complementary_limit(&self, reserve: &CachedReserveInfo, market_value: Number) -> Number {
         match Side::from_integer(self.side).expect("invalid side value") {
-            Side::Collateral => market_value / reserve.min_collateral_ratio,
+            // Both discounts are intended, and match the health check: the collateral
+            // factor discounts the collateral's value, while the minimum ratio is the
+            // margin that weighted collateral must keep over the debt.
+            Side::Collateral => {
+                market_value * reserve.collateral_factor / reserve.min_collateral_ratio
+            }
             Side::Loan => market_value * reserve.min_collateral_ratio,
         }
     }
@@ -565,6 +681,29 @@ mod tests {
         obligation: Obligation,
     }
 
+    /// Fill in the reserve parameters a test leaves unset, the way the reserve
+    /// would: positions are valued at the reserve's price, the full value counts
+    /// as collateral, and loans are liquidated at the minimum ratio.
+    fn default_reserve_params(reserve: &mut ReserveInfo) {
+        let cache = reserve.cache.get_stale_mut();
+
+        if cache.collateral_price == Number::ZERO {
+            cache.collateral_price = cache.price;
+        }
+
+        if cache.debt_price == Number::ZERO {
+            cache.debt_price = cache.price;
+        }
+
+        if cache.collateral_factor == Number::ZERO {
+            cache.collateral_factor = Number::ONE;
+        }
+
+        if cache.liquidation_collateral_ratio == Number::ZERO {
+            cache.liquidation_collateral_ratio = cache.min_collateral_ratio;
+        }
+    }
+
     impl ObligationTestContext {
         fn new() -> Self {
             Self {
@@ -581,6 +720,7 @@ mod tests {
             let reserve_info = self.market.get_mut(reserve_index);
 
             reserve_init(reserve_info);
+            default_reserve_params(reserve_info);
 
             self.obligation
                 .register_collateral(&collateral_key, reserve_index)
@@ -597,6 +737,7 @@ mod tests {
             let reserve_info = self.market.get_mut(reserve_index);
 
             reserve_init(reserve_info);
+            default_reserve_params(reserve_info);
 
             self.obligation
                 .register_loan(&loan_key, reserve_index)
@@ -643,6 +784,253 @@ mod tests {
         assert!(healthy);
     }
 
+    #[test]
+    fn liquidation_uses_separate_ratio() {
+        let mut ctx = ObligationTestContext::new();
+
+        let collateral = ctx.create_collateral(|reserve| {
+            let cache = reserve.cache.get_stale_mut();
+
+            cache.price = Number::from(1);
+            cache.deposit_note_exchange_rate = Number::from(1_000);
+            cache.collateral_factor = Number::ONE;
+        });
+        let loan = ctx.create_loan(|reserve| {
+            let cache = reserve.cache.get_stale_mut();
+
+            cache.price = Number::from(1);
+            cache.loan_note_exchange_rate = Number::from(1_000);
+            cache.min_collateral_ratio = Number::from_bps(15000);
+            cache.liquidation_collateral_ratio = Number::from_bps(12500);
+        });
+
+        ctx.obligation
+            .deposit_collateral(&collateral, Number::from(1_000_000))
+            .unwrap();
+        ctx.obligation.borrow(&loan, Number::from(750_000)).unwrap();
+
+        // c-ratio = 133%, too low to borrow more but not low enough to liquidate
+        ctx.obligation.cache_calculations(&ctx.market, 0);
+        assert!(!ctx.obligation.is_healthy(&ctx.market, 0));
+        assert!(!ctx.obligation.is_liquidatable(&ctx.market, 0));
+
+        // c-ratio = 111%
+        ctx.obligation.borrow(&loan, Number::from(150_000)).unwrap();
+
+        ctx.obligation.cache_calculations(&ctx.market, 0);
+        assert!(ctx.obligation.is_liquidatable(&ctx.market, 0));
+    }
+
+    #[test]
+    fn collateral_factor_discounts_health() {
+        let mut ctx = ObligationTestContext::new();
+
+        let stable = ctx.create_collateral(|reserve| {
+            let cache = reserve.cache.get_stale_mut();
+
+            cache.price = Number::from(1);
+            cache.deposit_note_exchange_rate = Number::from(1);
+            cache.collateral_factor = Number::ONE;
+        });
+        let volatile = ctx.create_collateral(|reserve| {
+            let cache = reserve.cache.get_stale_mut();
+
+            cache.price = Number::from(1);
+            cache.deposit_note_exchange_rate = Number::from(1);
+            cache.collateral_factor = Number::from_bps(5000);
+        });
+        let loan = ctx.create_loan(|reserve| {
+            let cache = reserve.cache.get_stale_mut();
+
+            cache.price = Number::from(1);
+            cache.loan_note_exchange_rate = Number::from(1);
+            cache.min_collateral_ratio = Number::from_bps(12500);
+            cache.liquidation_collateral_ratio = Number::from_bps(12500);
+        });
+
+        ctx.obligation
+            .deposit_collateral(&volatile, Number::from(1_000))
+            .unwrap();
+        ctx.obligation.borrow(&loan, Number::from(600)).unwrap();
+
+        // raw c-ratio = 166%, weighted c-ratio = 83%
+        ctx.obligation.cache_calculations(&ctx.market, 0);
+        assert_eq!(
+            ctx.obligation.collateral_value(&ctx.market, 0),
+            Number::from(1_000)
+        );
+        assert_eq!(
+            ctx.obligation.weighted_collateral_value(&ctx.market, 0),
+            Number::from(500)
+        );
+        assert!(!ctx.obligation.is_healthy(&ctx.market, 0));
+
+        // weighted c-ratio = 133%
+        ctx.obligation
+            .deposit_collateral(&stable, Number::from(300))
+            .unwrap();
+
+        ctx.obligation.cache_calculations(&ctx.market, 0);
+        assert!(ctx.obligation.is_healthy(&ctx.market, 0));
+    }
+
+    #[test]
+    fn restoring_sell_value_handles_high_collateral_factor() {
+        // min ratio 125%, 10% bonus, 50% factor:
+        // (1.25 * 1000 - 1000) / (1.25 / 1.1 - 0.5) = 392.86
+        let value = restoring_sell_value(
+            Number::from_bps(12500),
+            Number::from(1_000),
+            Number::from(1_000),
+            Number::from_bps(1000),
+            Number::from_bps(5000),
+        )
+        .unwrap();
+        assert_eq!(value.as_u64(0), 392);
+
+        // min ratio 110%, 20% bonus, 100% factor, where each sale removes more
+        // weighted collateral than it frees up from the loan
+        assert!(restoring_sell_value(
+            Number::from_bps(11000),
+            Number::from(1_000),
+            Number::from(1_000),
+            Number::from_bps(2000),
+            Number::ONE,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn category_ratio_applies_within_category() {
+        let mut ctx = ObligationTestContext::new();
+
+        let reserve_init = |category: u16| {
+            move |reserve: &mut ReserveInfo| {
+                let cache = reserve.cache.get_stale_mut();
+
+                cache.price = Number::from(1);
+                cache.deposit_note_exchange_rate = Number::from(1);
+                cache.loan_note_exchange_rate = Number::from(1);
+                cache.min_collateral_ratio = Number::from_bps(15000);
+                cache.liquidation_collateral_ratio = Number::from_bps(15000);
+                cache.collateral_factor = Number::ONE;
+                cache.category = category;
+                cache.category_min_collateral_ratio = Number::from_bps(10500);
+                cache.category_liquidation_collateral_ratio = Number::from_bps(10200);
+            }
+        };
+
+        let collateral = ctx.create_collateral(reserve_init(1));
+        let loan = ctx.create_loan(reserve_init(1));
+
+        ctx.obligation
+            .deposit_collateral(&collateral, Number::from(1_000))
+            .unwrap();
+        ctx.obligation.borrow(&loan, Number::from(900)).unwrap();
+
+        // c-ratio = 111%, only allowed when using the category's parameters
+        ctx.obligation.cache_calculations(&ctx.market, 0);
+        assert!(ctx.obligation.in_category(&ctx.market, 0));
+        assert!(ctx.obligation.is_healthy(&ctx.market, 0));
+
+        let other = ctx.create_collateral(reserve_init(2));
+        ctx.obligation
+            .deposit_collateral(&other, Number::from(1))
+            .unwrap();
+
+        ctx.obligation.cache_calculations(&ctx.market, 0);
+        assert!(!ctx.obligation.in_category(&ctx.market, 0));
+        assert!(!ctx.obligation.is_healthy(&ctx.market, 0));
+        assert!(ctx.obligation.is_liquidatable(&ctx.market, 0));
+    }
+
+    #[test]
+    fn empty_position_ignored_after_reserve_removed() {
+        let mut ctx = ObligationTestContext::new();
+
+        let reserve_init = |reserve: &mut ReserveInfo| {
+            let cache = reserve.cache.get_stale_mut();
+
+            cache.price = Number::from(1);
+            cache.deposit_note_exchange_rate = Number::from(1);
+            cache.loan_note_exchange_rate = Number::from(1);
+            cache.min_collateral_ratio = Number::from_bps(12500);
+            cache.liquidation_collateral_ratio = Number::from_bps(12500);
+            cache.collateral_factor = Number::ONE;
+        };
+
+        let collateral = ctx.create_collateral(reserve_init);
+        let loan = ctx.create_loan(reserve_init);
+        let emptied = ctx.create_collateral(reserve_init);
+
+        ctx.obligation
+            .deposit_collateral(&collateral, Number::from(1_000))
+            .unwrap();
+        ctx.obligation.borrow(&loan, Number::from(500)).unwrap();
+
+        // the emptied position's reserve is removed, and its entry left stale
+        let removed_index = ctx
+            .obligation
+            .collateral()
+            .position(&emptied)
+            .unwrap()
+            .reserve_index;
+        ctx.market.remove(removed_index);
+
+        ctx.obligation.cache_calculations(&ctx.market, 0);
+        assert!(ctx.obligation.is_healthy(&ctx.market, 0));
+        assert_eq!(
+            Number::from(1_000),
+            ctx.obligation.collateral_value(&ctx.market, 0)
+        );
+
+        // once the entry is reused, the empty position still adds no value
+        ctx.market.register(&Pubkey::new_unique()).unwrap();
+        reserve_init(ctx.market.get_mut(removed_index));
+        default_reserve_params(ctx.market.get_mut(removed_index));
+
+        ctx.obligation.cache_calculations(&ctx.market, 0);
+        assert_eq!(
+            Number::from(1_000),
+            ctx.obligation.collateral_value(&ctx.market, 0)
+        );
+    }
+
+    #[test]
+    fn valuation_uses_side_prices() {
+        let mut ctx = ObligationTestContext::new();
+
+        let collateral = ctx.create_collateral(|reserve| {
+            let cache = reserve.cache.get_stale_mut();
+
+            cache.price = Number::from(10);
+            cache.collateral_price = Number::from(9);
+            cache.debt_price = Number::from(11);
+            cache.deposit_note_exchange_rate = Number::from(1);
+            cache.collateral_factor = Number::ONE;
+        });
+        let loan = ctx.create_loan(|reserve| {
+            let cache = reserve.cache.get_stale_mut();
+
+            cache.price = Number::from(10);
+            cache.collateral_price = Number::from(9);
+            cache.debt_price = Number::from(11);
+            cache.loan_note_exchange_rate = Number::from(1);
+        });
+
+        ctx.obligation
+            .deposit_collateral(&collateral, Number::from(100))
+            .unwrap();
+        ctx.obligation.borrow(&loan, Number::from(50)).unwrap();
+
+        ctx.obligation.cache_calculations(&ctx.market, 0);
+        assert_eq!(
+            ctx.obligation.collateral_value(&ctx.market, 0),
+            Number::from(900)
+        );
+        assert_eq!(ctx.obligation.loan_value(&ctx.market, 0), Number::from(550));
+    }
+
     #[test]
     fn sane_liquidate_collateral() {
         let mut ctx = ObligationTestContext::new();
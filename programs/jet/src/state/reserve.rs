@@ -23,23 +23,32 @@ use std::cmp::Ordering;
 use jet_math::Number;
 use jet_proc_macros::assert_size;
 
+use crate::errors::ErrorCode;
 use crate::state::Cache;
 use crate::utils::FixedBuf;
-use crate::utils::JobCompletion;
 
 const SECONDS_PER_HOUR: UnixTimestamp = 3600;
-const SECONDS_PER_2H: UnixTimestamp = SECONDS_PER_HOUR * 2;
-const SECONDS_PER_12H: UnixTimestamp = SECONDS_PER_HOUR * 12;
 const SECONDS_PER_DAY: UnixTimestamp = SECONDS_PER_HOUR * 24;
-const SECONDS_PER_WEEK: UnixTimestamp = SECONDS_PER_DAY * 7;
 const SECONDS_PER_YEAR: UnixTimestamp = 31_536_000;
-const MAX_ACCRUAL_SECONDS: UnixTimestamp = SECONDS_PER_WEEK;
+
+/// The largest exponent for which interest is compounded directly with a series
+/// expansion, rather than by squaring the result for a smaller exponent.
+const MAX_SERIES_EXPONENT_BPS: u16 = 625;
+
+/// The number of terms used in the series expansion for compounding interest
+const SERIES_TERMS: usize = 8;
+
+/// The largest factor by which debt may grow in a single accrual
+const MAX_COMPOUND_GROWTH: u64 = 100_000_000;
+
+/// The most points that can be used to describe a reserve's interest rate curve
+pub const MAX_RATE_CURVE_KNOTS: usize = 8;
+
+/// The largest share of a liquidation premium that may be paid to the caller
+const MAX_KEEPER_BOUNTY_SHARE: u16 = 2_000;
 
 static_assertions::const_assert_eq!(SECONDS_PER_HOUR, 60 * 60);
-static_assertions::const_assert_eq!(SECONDS_PER_2H, 60 * 60 * 2);
-static_assertions::const_assert_eq!(SECONDS_PER_12H, 60 * 60 * 12);
 static_assertions::const_assert_eq!(SECONDS_PER_DAY, 60 * 60 * 24);
-static_assertions::const_assert_eq!(SECONDS_PER_WEEK, 60 * 60 * 24 * 7);
 static_assertions::const_assert_eq!(SECONDS_PER_YEAR, 60 * 60 * 24 * 365);
 
 /// We have three interest rate regimes. The rate is described by a continuous,
@@ -51,10 +60,18 @@ static_assertions::const_assert_eq!(SECONDS_PER_YEAR, 60 * 60 * 24 * 365);
 /// 3. [utilization_rate_2] to one: borrow rate increases linearly from
 ///     [borrow_rate_2] to [borrow_rate_3].
 ///
+/// Alternatively, the rate can be described by an arbitrary curve of up to
+/// [MAX_RATE_CURVE_KNOTS] points set in [rate_curve], in which case the
+/// regime parameters above are ignored.
+///
+/// With the adaptive [rate_model], the rate instead follows a curve around a
+/// rate at the [adaptive_target_utilization], which is raised over time while
+/// utilization is above the target and lowered while it's below.
+///
 /// Interest rates are nominal annual amounts, compounded continuously with
 /// a day-count convention of actual-over-365. The accrual period is determined
 /// by counting slots, and comparing against the number of slots per year.
-#[assert_size(aligns, 64)]
+#[assert_size(aligns, 128)]
 #[repr(C)]
 #[derive(Pod, Zeroable, Clone, Copy, AnchorDeserialize, AnchorSerialize)]
 pub struct ReserveConfig {
@@ -94,8 +111,8 @@ pub struct ReserveConfig {
     /// The fee rate applied as interest owed on new loans
     pub loan_origination_fee: u16,
 
-    /// unused
-    pub _reserved0: u16,
+    /// The fee rate charged on the amount borrowed through a flash loan
+    pub flash_loan_fee: u16,
 
     /// Represented as a percentage of the Price
     /// confidence values above this will not be accepted
@@ -105,7 +122,385 @@ pub struct ReserveConfig {
     /// liquidating assetr from this reserve as collateral.
     pub liquidation_dex_trade_max: u64,
 
-    pub _reserved1: [u8; 24],
+    /// The maximum total amount of tokens that may be deposited into the
+    /// reserve. No limit is applied when zero.
+    pub max_total_deposits: u64,
+
+    /// The maximum total amount of tokens that may be owed to the reserve
+    /// by borrowers. No limit is applied when zero.
+    pub max_outstanding_debt: u64,
+
+    /// The collateralization ratio below which a loan on this reserve may
+    /// be liquidated. Must not be above the `min_collateral_ratio`, and
+    /// when zero the `min_collateral_ratio` is used instead.
+    pub liquidation_collateral_ratio: u16,
+
+    /// The portion of the value of this reserve's tokens which counts
+    /// towards the collateral of an obligation. When zero the full value is
+    /// counted.
+    pub collateral_factor: u16,
+
+    /// The maximum number of slots since the oracle price was published,
+    /// before the price is considered stale. No limit is applied when zero.
+    pub max_oracle_age: u32,
+
+    /// The maximum difference allowed between the primary and secondary
+    /// oracle prices, relative to the primary price. No check is made when zero.
+    pub oracle_max_deviation: u16,
+
+    /// The price change, relative to the last accepted price, beyond which
+    /// borrows on the reserve are automatically halted. No check is made when zero.
+    pub price_halt_threshold: u16,
+
+    /// The number of slots since the last accepted price, within which a price
+    /// change beyond the `price_halt_threshold` halts borrows on the reserve.
+    pub price_halt_window: u32,
+
+    /// The number of points used from the `rate_curve`. When zero, the rate
+    /// is instead described by the three regimes.
+    pub rate_curve_len: u8,
+
+    /// The model used to determine the borrow rate, see [RateModel]
+    pub rate_model: u8,
+
+    /// The utilization rate the adaptive rate model steers towards
+    pub adaptive_target_utilization: u16,
+
+    /// The largest change per day to the adaptive rate at the target utilization,
+    /// applied in proportion to how far utilization is from the target.
+    pub adaptive_adjustment_speed: u16,
+
+    /// The multiple of the rate at the target utilization that is charged at
+    /// full utilization, and the fraction of it charged at zero utilization.
+    pub adaptive_curve_steepness: u16,
+
+    /// The lowest the adaptive rate at the target utilization may go
+    pub adaptive_min_rate: u16,
+
+    /// The highest the adaptive rate at the target utilization may go
+    pub adaptive_max_rate: u16,
+
+    /// The portion of the fees collected from interest which is set aside in
+    /// the reserve's insurance fund, to cover debts that can't be repaid.
+    pub insurance_fee_share: u16,
+
+    /// The portion of the liquidation premium earned when liquidating loans
+    /// from this reserve on the DEX, which is paid to the caller. Underwater
+    /// liquidations earn no premium, so they pay no bounty.
+    pub keeper_bounty_share: u16,
+
+    pub _reserved1: [u8; 8],
+
+    /// The points of a piecewise-linear interest rate curve, ordered by
+    /// utilization from zero to one.
+    pub rate_curve: [RateKnot; MAX_RATE_CURVE_KNOTS],
+}
+
+/// The ways a reserve's borrow rate can be determined
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateModel {
+    /// The rate follows the fixed curve described by the config
+    Curve,
+
+    /// The rate at the target utilization adjusts to demand over time
+    Adaptive,
+}
+
+/// A point on a reserve's interest rate curve
+#[assert_size(4)]
+#[derive(Pod, Zeroable, Clone, Copy, Debug, Default, AnchorDeserialize, AnchorSerialize)]
+#[repr(C)]
+pub struct RateKnot {
+    /// The utilization rate at this point
+    pub utilization_rate: u16,
+
+    /// The borrow rate charged at this utilization rate
+    pub borrow_rate: u16,
+}
+
+impl ReserveConfig {
+    /// Check that the config values are consistent with each other, and
+    /// can't cause errors when used for calculations within the reserve.
+    pub fn validate(&self) -> Result<(), ErrorCode> {
+        match self.rate_curve_len {
+            0 => self.validate_rate_regimes()?,
+            _ => self.validate_rate_curve()?,
+        }
+
+        if self.rate_model()? == RateModel::Adaptive {
+            self.validate_adaptive_rate()?;
+        }
+
+        if self.min_collateral_ratio <= 10_000 {
+            msg!("min_collateral_ratio must be above 100%");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        if (self.min_collateral_ratio as u32) <= 10_000 + self.liquidation_premium as u32 {
+            msg!("min_collateral_ratio must be above 100% plus the liquidation_premium");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        if self.liquidation_collateral_ratio != 0
+            && (self.liquidation_collateral_ratio > self.min_collateral_ratio
+                || (self.liquidation_collateral_ratio as u32)
+                    <= 10_000 + self.liquidation_premium as u32)
+        {
+            msg!("liquidation_collateral_ratio must be between 100% plus the liquidation_premium and the min_collateral_ratio");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        if self.collateral_factor > 10_000 {
+            msg!("collateral_factor cannot be above 100%");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        if self.manage_fee_rate > 10_000
+            || self.loan_origination_fee > 10_000
+            || self.flash_loan_fee > 10_000
+            || self.insurance_fee_share > 10_000
+        {
+            msg!("fee rates cannot be above 100%");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        if self.keeper_bounty_share > MAX_KEEPER_BOUNTY_SHARE {
+            msg!("keeper_bounty_share cannot be above 20%");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        Ok(())
+    }
+
+    fn validate_rate_regimes(&self) -> Result<(), ErrorCode> {
+        if self.utilization_rate_1 == 0 || self.utilization_rate_1 >= self.utilization_rate_2 {
+            msg!("utilization_rate_1 must be non-zero and less than utilization_rate_2");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        if self.utilization_rate_2 > 10_000 {
+            msg!("utilization_rate_2 cannot be above 100%");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        if self.borrow_rate_0 > self.borrow_rate_1
+            || self.borrow_rate_1 > self.borrow_rate_2
+            || self.borrow_rate_2 > self.borrow_rate_3
+        {
+            msg!("borrow rates must not decrease with utilization");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        Ok(())
+    }
+
+    fn validate_rate_curve(&self) -> Result<(), ErrorCode> {
+        let len = self.rate_curve_len as usize;
+
+        if !(2..=MAX_RATE_CURVE_KNOTS).contains(&len) {
+            msg!(
+                "rate_curve_len must be between 2 and {}",
+                MAX_RATE_CURVE_KNOTS
+            );
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        let knots = &self.rate_curve[..len];
+
+        if knots[0].utilization_rate != 0 || knots[len - 1].utilization_rate != 10_000 {
+            msg!("the rate curve must start at 0% and end at 100% utilization");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        for pair in knots.windows(2) {
+            if pair[0].utilization_rate >= pair[1].utilization_rate {
+                msg!("rate curve utilization rates must be increasing");
+                return Err(ErrorCode::InvalidParameter);
+            }
+
+            if pair[0].borrow_rate > pair[1].borrow_rate {
+                msg!("borrow rates must not decrease with utilization");
+                return Err(ErrorCode::InvalidParameter);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_adaptive_rate(&self) -> Result<(), ErrorCode> {
+        if self.adaptive_target_utilization == 0 || self.adaptive_target_utilization >= 10_000 {
+            msg!("adaptive_target_utilization must be between 0% and 100%");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        if self.adaptive_curve_steepness < 10_000 {
+            msg!("adaptive_curve_steepness cannot be below 100%");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        if self.adaptive_min_rate > self.adaptive_max_rate {
+            msg!("adaptive_min_rate cannot be above adaptive_max_rate");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        Ok(())
+    }
+
+    /// Get the model used to determine the borrow rate
+    pub fn rate_model(&self) -> Result<RateModel, ErrorCode> {
+        match self.rate_model {
+            0 => Ok(RateModel::Curve),
+            1 => Ok(RateModel::Adaptive),
+            _ => {
+                msg!("unknown rate_model {}", self.rate_model);
+                Err(ErrorCode::InvalidParameter)
+            }
+        }
+    }
+
+    /// Get the points describing the interest rate curve, with the regime
+    /// parameters converted into the equivalent points when no curve is set.
+    pub fn rate_curve(&self) -> ([RateKnot; MAX_RATE_CURVE_KNOTS], usize) {
+        if self.rate_curve_len != 0 {
+            return (self.rate_curve, self.rate_curve_len as usize);
+        }
+
+        let mut knots = [RateKnot::default(); MAX_RATE_CURVE_KNOTS];
+        let points = [
+            (0, self.borrow_rate_0),
+            (self.utilization_rate_1, self.borrow_rate_1),
+            (self.utilization_rate_2, self.borrow_rate_2),
+            (10_000, self.borrow_rate_3),
+        ];
+
+        for (knot, (utilization_rate, borrow_rate)) in knots.iter_mut().zip(points) {
+            *knot = RateKnot {
+                utilization_rate,
+                borrow_rate,
+            };
+        }
+
+        (knots, points.len())
+    }
+
+    /// The collateralization ratio below which loans can be liquidated
+    pub fn liquidation_collateral_ratio(&self) -> u16 {
+        match self.liquidation_collateral_ratio {
+            0 => self.min_collateral_ratio,
+            ratio => ratio,
+        }
+    }
+
+    /// The portion of the value of the tokens counted as collateral
+    pub fn collateral_factor(&self) -> u16 {
+        match self.collateral_factor {
+            0 => 10_000,
+            factor => factor,
+        }
+    }
+
+    /// Check that a new config only differs from this one in the parameters
+    /// that a market's risk admin is allowed to adjust, and that none of
+    /// them move by more than `max_change` basis points of their current value.
+    /// Disabled checks and limits may be enabled at any value.
+    pub fn verify_risk_change(
+        &self,
+        new_config: &ReserveConfig,
+        max_change: u16,
+    ) -> Result<(), ErrorCode> {
+        let mut unchanged = *new_config;
+
+        unchanged.min_collateral_ratio = self.min_collateral_ratio;
+        unchanged.liquidation_premium = self.liquidation_premium;
+        unchanged.liquidation_collateral_ratio = self.liquidation_collateral_ratio;
+        unchanged.collateral_factor = self.collateral_factor;
+        unchanged.confidence_threshold = self.confidence_threshold;
+        unchanged.max_oracle_age = self.max_oracle_age;
+        unchanged.oracle_max_deviation = self.oracle_max_deviation;
+        unchanged.price_halt_threshold = self.price_halt_threshold;
+        unchanged.price_halt_window = self.price_halt_window;
+        unchanged.liquidation_dex_trade_max = self.liquidation_dex_trade_max;
+        unchanged.max_total_deposits = self.max_total_deposits;
+        unchanged.max_outstanding_debt = self.max_outstanding_debt;
+
+        if bytemuck::bytes_of(&unchanged) != bytemuck::bytes_of(self) {
+            msg!(
+                "the risk admin may only change collateral, liquidation, oracle and cap parameters"
+            );
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        // Parameters where zero means a default are compared by the values in effect
+        let changes = [
+            (
+                self.min_collateral_ratio as u64,
+                new_config.min_collateral_ratio as u64,
+            ),
+            (
+                self.liquidation_premium as u64,
+                new_config.liquidation_premium as u64,
+            ),
+            (
+                self.liquidation_collateral_ratio() as u64,
+                new_config.liquidation_collateral_ratio() as u64,
+            ),
+            (
+                self.collateral_factor() as u64,
+                new_config.collateral_factor() as u64,
+            ),
+            (
+                self.confidence_threshold as u64,
+                new_config.confidence_threshold as u64,
+            ),
+        ];
+
+        // Checks and limits where zero means disabled may be enabled at any
+        // value, since that only makes the reserve safer, but never disabled
+        let limits = [
+            (self.max_oracle_age as u64, new_config.max_oracle_age as u64),
+            (
+                self.oracle_max_deviation as u64,
+                new_config.oracle_max_deviation as u64,
+            ),
+            (
+                self.price_halt_threshold as u64,
+                new_config.price_halt_threshold as u64,
+            ),
+            (
+                self.price_halt_window as u64,
+                new_config.price_halt_window as u64,
+            ),
+            (
+                self.liquidation_dex_trade_max,
+                new_config.liquidation_dex_trade_max,
+            ),
+            (self.max_total_deposits, new_config.max_total_deposits),
+            (self.max_outstanding_debt, new_config.max_outstanding_debt),
+        ];
+
+        for &(current, new) in &limits {
+            if current != 0 && new == 0 {
+                msg!("the risk admin may not disable a check or remove a limit");
+                return Err(ErrorCode::InvalidParameter);
+            }
+        }
+
+        let bounded_limits = limits.iter().copied().filter(|(current, _)| *current != 0);
+
+        for (current, new) in changes.iter().copied().chain(bounded_limits) {
+            let difference = std::cmp::max(new, current) - std::cmp::min(new, current);
+
+            if difference as u128 * 10_000 > current as u128 * max_change as u128 {
+                msg!(
+                    "the risk admin may only change a parameter by {} bps",
+                    max_change
+                );
+                return Err(ErrorCode::InvalidParameter);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[assert_size(2048)]
@@ -152,11 +547,39 @@ pub struct Reserve {
     /// The DEX market account that this reserve can trade in
     pub dex_market: Pubkey,
 
-    pub _reserved0: [u8; 408],
+    /// Storage for flags that can be set on the reserve.
+    pub flags: u64,
+
+    /// The time at which the pending config may be applied to the reserve.
+    /// Zero when there is no pending config.
+    pub pending_config_activation: UnixTimestamp,
+
+    /// A config change that has been queued, but not yet applied
+    pub pending_config: ReserveConfig,
+
+    /// The asset category in the market this reserve belongs to, or zero for none
+    pub category: u16,
+
+    /// An additional price account for the token, used to check the primary
+    /// oracle or to replace it when it's stale. Unused when zero.
+    pub secondary_oracle_price: Pubkey,
+
+    /// Set while a flash loan from the reserve is outstanding, during which
+    /// no other instruction may use the reserve.
+    pub flash_loan_lock: u8,
+
+    /// The time at which the pending category may be applied to the reserve.
+    /// Zero when there is no pending category.
+    pub pending_category_activation: UnixTimestamp,
+
+    /// A category change that has been queued, but not yet applied
+    pub pending_category: u16,
+
+    pub _reserved0: [u8; 219],
 
     pub config: ReserveConfig,
 
-    _reserved1: [u8; 704],
+    _reserved1: [u8; 640],
 
     state: [u8; 512],
 }
@@ -182,6 +605,227 @@ impl Reserve {
         self.state().get_stale().total_loan_notes
     }
 
+    /// Get the current flags set on the reserve
+    pub fn flags(&self) -> ReserveFlags {
+        ReserveFlags::from_bits(self.flags).unwrap()
+    }
+
+    /// Set new flags on the reserve
+    pub fn reset_flags(&mut self, flags: ReserveFlags) {
+        self.flags = flags.bits();
+    }
+
+    /// Record a newly accepted price for the reserve's token. If the price moved
+    /// too far from the price at the start of the configured window, then borrows
+    /// are halted on the reserve until the owner clears the flag.
+    ///
+    /// Returns true when the price move caused borrows to be halted.
+    pub fn record_price(&mut self, current_slot: u64, price: Number) -> bool {
+        let threshold = self.config.price_halt_threshold;
+        let window = self.config.price_halt_window as u64;
+        let state = self.state_mut().get_stale_mut();
+
+        let reference_price = state.reference_price;
+        let window_expired = current_slot.saturating_sub(state.reference_price_slot) > window;
+
+        if reference_price == Number::ZERO {
+            state.reference_price = price;
+            state.reference_price_slot = current_slot;
+            return false;
+        }
+
+        // Start a new window once the current one has passed, so that a price
+        // can't drift away through a series of smaller moves. The new price is
+        // still checked against the old reference, so that a reserve refreshed
+        // less often than the window can't skip the check.
+        if window_expired {
+            state.reference_price = price;
+            state.reference_price_slot = current_slot;
+        }
+
+        if threshold == 0 {
+            return false;
+        }
+
+        let change = match price > reference_price {
+            true => price - reference_price,
+            false => reference_price - price,
+        };
+
+        if change <= Number::from_bps(threshold) * reference_price {
+            return false;
+        }
+
+        msg!(
+            "price moved from {} to {}, halting borrows",
+            reference_price,
+            price
+        );
+        self.reset_flags(self.flags() | ReserveFlags::HALT_BORROWS);
+
+        true
+    }
+
+    /// Verify that the reserve is still accepting new deposits and borrows
+    pub fn verify_not_deprecated(&self) -> Result<(), ErrorCode> {
+        if self.flags().contains(ReserveFlags::DEPRECATED) {
+            msg!("the reserve is deprecated");
+            return Err(ErrorCode::ReserveDeprecated);
+        }
+
+        Ok(())
+    }
+
+    /// Verify that the reserve isn't in the middle of a flash loan
+    pub fn verify_unlocked(&self) -> Result<(), ErrorCode> {
+        if self.flash_loan_lock != 0 {
+            msg!("the reserve has an outstanding flash loan");
+            return Err(ErrorCode::ReserveLocked);
+        }
+
+        Ok(())
+    }
+
+    /// Lock the reserve for the duration of a flash loan. The reserve must be
+    /// refreshed, so that the fees can be booked once the loan is repaid.
+    pub fn begin_flash_loan(&mut self, current_slot: u64) -> Result<(), ErrorCode> {
+        self.verify_unlocked()?;
+
+        if self.state().validate_fresh(current_slot).is_err() {
+            msg!("the reserve must be refreshed before a flash loan");
+            return Err(ErrorCode::ReserveStale);
+        }
+
+        self.flash_loan_lock = 1;
+
+        Ok(())
+    }
+
+    /// Check that a flash loan was repaid with its fees, and release the
+    /// lock on the reserve.
+    pub fn end_flash_loan(
+        &mut self,
+        current_slot: u64,
+        vault_tokens_before: u64,
+        vault_tokens_after: u64,
+        fees: u64,
+    ) -> Result<(), ErrorCode> {
+        let vault_tokens_required = vault_tokens_before
+            .checked_add(fees)
+            .ok_or(ErrorCode::ArithmeticError)?;
+
+        if vault_tokens_after < vault_tokens_required {
+            msg!(
+                "vault balance = {}, required = {}",
+                vault_tokens_after,
+                vault_tokens_required
+            );
+            return Err(ErrorCode::FlashLoanNotRepaid);
+        }
+
+        self.add_uncollected_fees(current_slot, fees);
+        self.flash_loan_lock = 0;
+
+        Ok(())
+    }
+
+    /// Verify that the reserve has been deprecated, and has no remaining
+    /// deposit or loan notes, so that it can be removed from the market.
+    /// Tokens left in the vault from rounding, uncollected fees or the
+    /// insurance fund don't prevent removal, since they belong to no depositor.
+    pub fn verify_removable(&self, fee_notes: u64) -> Result<(), ErrorCode> {
+        if !self.flags().contains(ReserveFlags::DEPRECATED) {
+            msg!("the reserve must be deprecated before it can be removed");
+            return Err(ErrorCode::ReserveNotRemovable);
+        }
+
+        if self.total_deposit_notes() != 0 || self.total_loan_notes() != 0 {
+            msg!("the reserve still has deposits or loans");
+            return Err(ErrorCode::ReserveNotRemovable);
+        }
+
+        if fee_notes != 0 {
+            msg!("the reserve still has fee notes to be collected");
+            return Err(ErrorCode::ReserveNotRemovable);
+        }
+
+        Ok(())
+    }
+
+    /// Check that a config can be used with the reserve's oracles. A deviation
+    /// limit is required whenever a secondary oracle is set.
+    pub fn verify_oracle_config(&self, config: &ReserveConfig) -> Result<(), ErrorCode> {
+        if self.secondary_oracle_price != Pubkey::default() && config.oracle_max_deviation == 0 {
+            msg!("oracle_max_deviation must be set when the reserve has a secondary oracle");
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        Ok(())
+    }
+
+    /// Queue a new config to be applied to the reserve at a later time
+    pub fn queue_config(&mut self, config: ReserveConfig, activation: UnixTimestamp) {
+        self.pending_config = config;
+        self.pending_config_activation = activation;
+    }
+
+    /// Replace the current config with the pending one, if it's ready
+    pub fn apply_pending_config(&mut self, current_time: UnixTimestamp) -> Result<(), ErrorCode> {
+        let activation = self.pending_config_activation;
+
+        if activation == 0 {
+            return Err(ErrorCode::NoPendingReserveConfig);
+        }
+
+        if current_time < activation {
+            msg!("pending config can be applied after {}", activation);
+            return Err(ErrorCode::PendingReserveConfigNotReady);
+        }
+
+        let pending_config = self.pending_config;
+        self.verify_oracle_config(&pending_config)?;
+        self.config = pending_config;
+        self.cancel_pending_config();
+
+        Ok(())
+    }
+
+    /// Discard the pending config change
+    pub fn cancel_pending_config(&mut self) {
+        self.pending_config = ReserveConfig::zeroed();
+        self.pending_config_activation = 0;
+    }
+
+    pub fn queue_category(&mut self, category: u16, activation: UnixTimestamp) {
+        self.pending_category = category;
+        self.pending_category_activation = activation;
+    }
+
+    /// Replace the current category with the pending one, if it's ready
+    pub fn apply_pending_category(&mut self, current_time: UnixTimestamp) -> Result<(), ErrorCode> {
+        let activation = self.pending_category_activation;
+
+        if activation == 0 {
+            return Err(ErrorCode::NoPendingReserveCategory);
+        }
+
+        if current_time < activation {
+            msg!("pending category can be applied after {}", activation);
+            return Err(ErrorCode::PendingReserveCategoryNotReady);
+        }
+
+        self.category = self.pending_category;
+        self.cancel_pending_category();
+
+        Ok(())
+    }
+
+    /// Discard the pending category change
+    pub fn cancel_pending_category(&mut self) {
+        self.pending_category = 0;
+        self.pending_category_activation = 0;
+    }
+
     pub fn unwrap_outstanding_debt(&self, current_slot: u64) -> &Number {
         &self.unwrap_state(current_slot).outstanding_debt
     }
@@ -225,6 +869,52 @@ impl Reserve {
         state.total_deposit_notes = state.total_deposit_notes.checked_sub(note_amount).unwrap();
     }
 
+    /// Verify that depositing an amount of tokens would not exceed
+    /// the limit on total deposits configured for the reserve.
+    pub fn verify_deposit_cap(&self, token_amount: u64) -> Result<(), ErrorCode> {
+        let max_total_deposits = self.config.max_total_deposits;
+
+        if max_total_deposits == 0 {
+            return Ok(());
+        }
+
+        let total_deposits = self.total_deposits().saturating_add(token_amount);
+
+        if total_deposits > max_total_deposits {
+            msg!(
+                "deposit of {} would exceed the reserve deposit cap of {}",
+                token_amount,
+                max_total_deposits
+            );
+            return Err(ErrorCode::DepositCapExceeded);
+        }
+
+        Ok(())
+    }
+
+    /// Verify that borrowing an amount of tokens would not exceed
+    /// the limit on outstanding debt configured for the reserve.
+    pub fn verify_borrow_cap(&self, current_slot: u64, token_amount: u64) -> Result<(), ErrorCode> {
+        let max_outstanding_debt = Number::from(self.config.max_outstanding_debt);
+
+        if max_outstanding_debt == Number::ZERO {
+            return Ok(());
+        }
+
+        let outstanding_debt = *self.unwrap_outstanding_debt(current_slot);
+
+        if outstanding_debt + Number::from(token_amount) > max_outstanding_debt {
+            msg!(
+                "borrow of {} would exceed the reserve debt cap of {}",
+                token_amount,
+                max_outstanding_debt
+            );
+            return Err(ErrorCode::BorrowCapExceeded);
+        }
+
+        Ok(())
+    }
+
     /// Calculates the borrow fee token amount for
     /// an amount of tokens to be borrowed from the reserve.
     pub fn borrow_fee(&self, token_amount: u64) -> u64 {
@@ -234,6 +924,27 @@ impl Reserve {
         fee_owed.as_u64_ceil(0)
     }
 
+    /// The collateralization ratio below which loans on this reserve can be liquidated
+    pub fn liquidation_collateral_ratio(&self) -> u16 {
+        let config = self.config;
+        config.liquidation_collateral_ratio()
+    }
+
+    /// The portion of the value of this reserve's tokens counted as collateral
+    pub fn collateral_factor(&self) -> u16 {
+        let config = self.config;
+        config.collateral_factor()
+    }
+
+    /// Calculates the fee token amount owed for
+    /// an amount of tokens to be flash loaned from the reserve.
+    pub fn flash_loan_fee(&self, token_amount: u64) -> u64 {
+        let flash_loan_fee = Number::from_bps(self.config.flash_loan_fee);
+        let fee_owed = flash_loan_fee * token_amount;
+
+        fee_owed.as_u64_ceil(0)
+    }
+
     /// Record an amount of tokens to be borrowed from the reserve.
     pub fn borrow(&mut self, current_slot: u64, token_amount: u64, note_amount: u64, fees: u64) {
         let borrowed_amount = Number::from(token_amount);
@@ -262,6 +973,79 @@ impl Reserve {
         }
     }
 
+    /// Remove debt that will never be repaid from the reserve, covering as much
+    /// of it as possible from the insurance fund. The remainder is lost by
+    /// depositors, through a lower deposit note exchange rate, and is recorded
+    /// as unrecovered debt.
+    ///
+    /// Returns the amount of debt removed, and the amount of it covered by the fund.
+    pub fn write_off_debt(
+        &mut self,
+        current_slot: u64,
+        note_amount: u64,
+    ) -> Result<(Number, Number), ErrorCode> {
+        let state = self.unwrap_state_mut(current_slot);
+        let calc = LoanNoteCalculator {
+            outstanding_debt: state.outstanding_debt,
+            mint_supply: state.total_loan_notes,
+        };
+
+        let debt = std::cmp::min(
+            Number::from(note_amount) * calc.exchange_rate(),
+            state.outstanding_debt,
+        );
+        let covered = std::cmp::min(debt, state.insurance_fund);
+
+        state.total_loan_notes = state
+            .total_loan_notes
+            .checked_sub(note_amount)
+            .ok_or(ErrorCode::ArithmeticError)?;
+        state.outstanding_debt -= debt;
+        state.insurance_fund -= covered;
+        state.unrecovered_debt += debt - covered;
+
+        if state.total_loan_notes == 0 && state.outstanding_debt < Number::ONE {
+            // Truncate any leftover fraction from debts
+            state.outstanding_debt = Number::ZERO;
+        }
+
+        Ok((debt, covered))
+    }
+
+    /// Remove tokens from the insurance fund, so they can be moved out of the vault
+    pub fn release_insurance(
+        &mut self,
+        current_slot: u64,
+        token_amount: u64,
+    ) -> Result<(), ErrorCode> {
+        let state = self.unwrap_state_mut(current_slot);
+        let amount = Number::from(token_amount);
+
+        if amount > state.insurance_fund {
+            msg!(
+                "the insurance fund only holds {} tokens",
+                state.insurance_fund
+            );
+            return Err(ErrorCode::InvalidParameter);
+        }
+
+        state.insurance_fund -= amount;
+        Ok(())
+    }
+
+    /// Get the total debt written off that the insurance fund couldn't cover
+    pub fn unrecovered_debt(&self) -> Number {
+        self.state().get_stale().unrecovered_debt
+    }
+
+    /// Get the bounty paid to the caller of a DEX liquidation, out of the
+    /// premium earned from the liquidation.
+    pub fn keeper_bounty(&self, premium: u64) -> u64 {
+        let share = Number::from_bps(self.config.keeper_bounty_share);
+
+        (share * premium).as_u64(0)
+    }
+
     /// Record an amount of tokens added to the vault which need
     /// to be collected as fees later.
     pub fn add_uncollected_fees(&mut self, current_slot: u64, amount: u64) {
@@ -281,6 +1065,7 @@ impl Reserve {
         let calc = DepositNoteCalculator {
             outstanding_debt: state.outstanding_debt,
             uncollected_fees: state.uncollected_fees,
+            insurance_fund: state.insurance_fund,
             vault_total,
             mint_supply,
         };
@@ -305,17 +1090,22 @@ impl Reserve {
         vault_total: u64,
         target_time: UnixTimestamp,
         target_slot: u64,
-    ) -> JobCompletion {
+    ) -> Result<(), ErrorCode> {
         let ReserveState {
             outstanding_debt,
             accrued_until,
             ..
         } = *self.state().get_stale();
 
-        let time_behind = target_time - accrued_until;
-        let time_to_accrue = std::cmp::min(time_behind, MAX_ACCRUAL_SECONDS);
+        let time_to_accrue = target_time - accrued_until;
 
         let interest_rate = self.interest_rate(outstanding_debt, vault_total);
+
+        let config = self.config;
+        if time_to_accrue > 0 && matches!(config.rate_model(), Ok(RateModel::Adaptive)) {
+            self.adjust_adaptive_rate(outstanding_debt, vault_total, time_to_accrue);
+        }
+
         let state_cache: &mut Cache<ReserveState, 0> = bytemuck::from_bytes_mut(&mut self.state);
 
         match time_to_accrue.cmp(&0) {
@@ -324,27 +1114,25 @@ impl Reserve {
             }
             Ordering::Equal => {}
             Ordering::Greater => {
-                let compound_rate = Reserve::compound_interest(interest_rate, time_to_accrue);
+                let compound_rate = Reserve::compound_interest(interest_rate, time_to_accrue)?;
 
                 let interest_fee_rate = Number::from_bps(self.config.manage_fee_rate);
                 let state = state_cache.get_stale_mut();
 
                 let new_interest_accrued = state.outstanding_debt * compound_rate;
                 let fee_to_collect = new_interest_accrued * interest_fee_rate;
+                let insurance_to_collect =
+                    fee_to_collect * Number::from_bps(config.insurance_fee_share);
 
                 state.outstanding_debt += new_interest_accrued;
-                state.uncollected_fees += fee_to_collect;
-                state.accrued_until = state.accrued_until.checked_add(time_to_accrue).unwrap();
+                state.uncollected_fees += fee_to_collect - insurance_to_collect;
+                state.insurance_fund += insurance_to_collect;
+                state.accrued_until = target_time;
             }
         }
 
-        if time_behind == time_to_accrue {
-            state_cache.refresh_to(target_slot);
-            JobCompletion::Full
-        } else {
-            state_cache.invalidate();
-            JobCompletion::Partial
-        }
+        state_cache.refresh_to(target_slot);
+        Ok(())
     }
 
     /// Collect any fees that were accumulated
@@ -368,72 +1156,155 @@ impl Reserve {
     }
 
     /// Computes the effective applicable interest rate assuming continuous
-    /// compounding for the given number of slots.
+    /// compounding for the given number of seconds.
     ///
-    /// Uses an approximation calibrated for accuracy to twenty decimals places,
-    /// though the current configuration of Number does not support that. (TODO)
-    fn compound_interest(rate: Number, seconds: UnixTimestamp) -> Number {
-        // The two panics below are implementation details, chosen to facilitate convenient
-        // implementation of compounding. They can be relaxed with a bit of additional work.
-        // The "seconds" guards are chosen to guarantee accuracy under the assumption that
-        // the rate is not more than one.
-
-        if rate > Number::ONE * 2 {
-            panic!("Not implemented; interest rate too large for compound_interest()");
-        }
-
-        let terms = match seconds {
-            _ if seconds <= SECONDS_PER_2H => 5,
-            _ if seconds <= SECONDS_PER_12H => 6,
-            _ if seconds <= SECONDS_PER_DAY => 7,
-            _ if seconds <= SECONDS_PER_WEEK => 10,
-            _ => panic!("Not implemented; too many seconds in compound_interest()"),
-        };
+    /// Large exponents are halved until a short series is accurate for them,
+    /// and the result is then squared back up, so that any rate can be
+    /// compounded over any period. Fails if the growth is too large to represent.
+    fn compound_interest(rate: Number, seconds: UnixTimestamp) -> Result<Number, ErrorCode> {
+        let max_series_exponent = Number::from_bps(MAX_SERIES_EXPONENT_BPS);
+        let max_growth = Number::from(MAX_COMPOUND_GROWTH);
+
+        let mut x = rate * seconds / SECONDS_PER_YEAR;
+        let mut halvings = 0;
+
+        while x > max_series_exponent {
+            x = x / 2;
+            halvings += 1;
+        }
+
+        let mut growth = jet_math::expm1_approx(x, SERIES_TERMS);
 
-        let x = rate * seconds / SECONDS_PER_YEAR;
+        for _ in 0..halvings {
+            if growth > max_growth {
+                msg!("interest growth too large to compound");
+                return Err(ErrorCode::ArithmeticError);
+            }
+
+            // exp(2x) - 1 = (exp(x) - 1) * (exp(x) + 1)
+            growth = growth * (growth + Number::from(2));
+        }
+
+        if growth > max_growth {
+            msg!("interest growth too large to compound");
+            return Err(ErrorCode::ArithmeticError);
+        }
 
-        jet_math::expm1_approx(x, terms)
+        Ok(growth)
     }
 
     /// Get the interest rate charged to borrowers for the given inputs
     pub fn interest_rate(&self, outstanding_debt: Number, vault_total: u64) -> Number {
-        let borrow_1 = Number::from_bps(self.config.borrow_rate_1);
+        let config = self.config;
+
+        if let Ok(RateModel::Adaptive) = config.rate_model() {
+            return self.adaptive_interest_rate(outstanding_debt, vault_total);
+        }
+
+        let (knots, len) = config.rate_curve();
+        let knots = &knots[..len];
 
         // Catch the edge case of empty reserve
         if vault_total == 0 && outstanding_debt == Number::ZERO {
-            return borrow_1;
+            return Number::from_bps(knots[1].borrow_rate);
         }
 
         let util_rate = utilization_rate(outstanding_debt, vault_total);
 
-        let util_1 = Number::from_bps(self.config.utilization_rate_1);
+        for pair in knots.windows(2) {
+            let util_end = Number::from_bps(pair[1].utilization_rate);
+
+            if util_rate <= util_end {
+                return Reserve::interpolate(
+                    util_rate,
+                    Number::from_bps(pair[0].utilization_rate),
+                    util_end,
+                    Number::from_bps(pair[0].borrow_rate),
+                    Number::from_bps(pair[1].borrow_rate),
+                );
+            }
+        }
 
-        if util_rate <= util_1 {
-            // First regime
-            let borrow_0 = Number::from_bps(self.config.borrow_rate_0);
+        // Maximum interest
+        Number::from_bps(knots[len - 1].borrow_rate)
+    }
 
-            return Reserve::interpolate(util_rate, Number::ZERO, util_1, borrow_0, borrow_1);
-        }
+    /// Get the current borrow rate at the target utilization for the adaptive model
+    fn adaptive_rate_at_target(&self) -> Number {
+        let min_rate = Number::from_bps(self.config.adaptive_min_rate);
+        let max_rate = Number::from_bps(self.config.adaptive_max_rate);
+        let rate = self.state().get_stale().adaptive_rate;
 
-        let util_2 = Number::from_bps(self.config.utilization_rate_2);
-        let borrow_2 = Number::from_bps(self.config.borrow_rate_2);
+        std::cmp::min(std::cmp::max(rate, min_rate), max_rate)
+    }
 
-        if util_rate <= util_2 {
-            // Second regime
-            let borrow_1 = Number::from_bps(self.config.borrow_rate_1);
+    /// Get the interest rate charged to borrowers under the adaptive model
+    fn adaptive_interest_rate(&self, outstanding_debt: Number, vault_total: u64) -> Number {
+        let rate_at_target = self.adaptive_rate_at_target();
+        let steepness = Number::from_bps(self.config.adaptive_curve_steepness);
+        let target = Number::from_bps(self.config.adaptive_target_utilization);
 
-            return Reserve::interpolate(util_rate, util_1, util_2, borrow_1, borrow_2);
+        // Catch the edge case of empty reserve
+        if vault_total == 0 && outstanding_debt == Number::ZERO {
+            return rate_at_target / steepness;
         }
 
-        let borrow_3 = Number::from_bps(self.config.borrow_rate_3);
+        let util_rate = std::cmp::min(utilization_rate(outstanding_debt, vault_total), Number::ONE);
 
-        if util_rate < Number::ONE {
-            // Third regime
-            return Reserve::interpolate(util_rate, util_2, Number::ONE, borrow_2, borrow_3);
+        if util_rate <= target {
+            Reserve::interpolate(
+                util_rate,
+                Number::ZERO,
+                target,
+                rate_at_target / steepness,
+                rate_at_target,
+            )
+        } else {
+            Reserve::interpolate(
+                util_rate,
+                target,
+                Number::ONE,
+                rate_at_target,
+                rate_at_target * steepness,
+            )
         }
+    }
 
-        // Maximum interest
-        borrow_3
+    /// Move the adaptive rate at the target utilization towards the current
+    /// demand, in proportion to how far utilization has been from the target.
+    fn adjust_adaptive_rate(
+        &mut self,
+        outstanding_debt: Number,
+        vault_total: u64,
+        seconds: UnixTimestamp,
+    ) {
+        let rate_at_target = self.adaptive_rate_at_target();
+        let target = Number::from_bps(self.config.adaptive_target_utilization);
+        let speed = Number::from_bps(self.config.adaptive_adjustment_speed);
+
+        let util_rate = match vault_total == 0 && outstanding_debt == Number::ZERO {
+            true => Number::ZERO,
+            false => std::cmp::min(utilization_rate(outstanding_debt, vault_total), Number::ONE),
+        };
+
+        let period = Number::from(seconds as u64) / Number::from(SECONDS_PER_DAY as u64);
+
+        let adjusted_rate = match util_rate > target {
+            true => {
+                let error = (util_rate - target) / (Number::ONE - target);
+                rate_at_target + speed * error * period
+            }
+            false => {
+                let error = (target - util_rate) / target;
+                rate_at_target.saturating_sub(speed * error * period)
+            }
+        };
+
+        let min_rate = Number::from_bps(self.config.adaptive_min_rate);
+        let max_rate = Number::from_bps(self.config.adaptive_max_rate);
+
+        self.state_mut().get_stale_mut().adaptive_rate =
+            std::cmp::min(std::cmp::max(adjusted_rate, min_rate), max_rate);
     }
 
     /// Linear interpolation between (x0, y0) and (x1, y1).
@@ -445,6 +1316,28 @@ impl Reserve {
     }
 }
 
+bitflags::bitflags! {
+    pub struct ReserveFlags: u64 {
+        /// Disable all borrowing and collateral withdrawals
+        const HALT_BORROWS = 1 << 0;
+
+        /// Disable repaying loans
+        const HALT_REPAYS = 1 << 1;
+
+        /// Disable deposits + withdrawals
+        const HALT_DEPOSITS = 1 << 2;
+
+        /// Disable all operations
+        const HALT_ALL = Self::HALT_BORROWS.bits
+                       | Self::HALT_REPAYS.bits
+                       | Self::HALT_DEPOSITS.bits;
+
+        /// Disable new deposits and borrows, so the reserve can be wound
+        /// down and removed from the market
+        const DEPRECATED = 1 << 3;
+    }
+}
+
 /// Information about a single collateral or loan account registered with an obligation
 #[assert_size(aligns, 496)]
 #[derive(Pod, Zeroable, Clone, Copy)]
@@ -461,7 +1354,24 @@ struct ReserveState {
     total_deposit_notes: u64,
     total_loan_notes: u64,
 
-    _reserved: FixedBuf<416>,
+    /// The price at the start of the current price halt window, and when
+    /// the window started
+    reference_price: Number,
+    reference_price_slot: u64,
+
+    /// The borrow rate at the target utilization, for the adaptive rate model
+    adaptive_rate: Number,
+
+    /// The tokens owed to the reserve's insurance fund, which are not
+    /// available to depositors. The fund is used up by debt write-offs, and
+    /// may be released to the treasury by the market owner.
+    insurance_fund: Number,
+
+    /// The total debt written off that the insurance fund couldn't cover,
+    /// which was lost by depositors
+    unrecovered_debt: Number,
+
+    _reserved: FixedBuf<312>,
 }
 
 /// Get the current utilization rate (borrowed / deposited)
@@ -472,6 +1382,7 @@ pub fn utilization_rate(outstanding_debt: Number, vault_total: u64) -> Number {
 struct DepositNoteCalculator {
     outstanding_debt: Number,
     uncollected_fees: Number,
+    insurance_fund: Number,
     vault_total: u64,
     mint_supply: u64,
 }
@@ -486,7 +1397,11 @@ impl<'a> NoteCalculator for DepositNoteCalculator {
         // the total debt owed to depositors, which may be less than the total
         // debt due to fees charged by the protocol. This allows the program
         // to generate new deposit notes based on the extra debt.
-        self.outstanding_debt + Number::from(self.vault_total) - self.uncollected_fees
+        // Tokens in the insurance fund are likewise excluded, since they're
+        // held back to cover any losses on loans.
+        self.outstanding_debt + Number::from(self.vault_total)
+            - self.uncollected_fees
+            - self.insurance_fund
     }
 }
 
@@ -548,6 +1463,7 @@ impl std::fmt::Debug for ReserveState {
             .field("accrued_until", &self.accrued_until)
             .field("outstanding_debt", &self.outstanding_debt.to_string())
             .field("uncollected_fees", &self.uncollected_fees.to_string())
+            .field("insurance_fund", &self.insurance_fund.to_string())
             .field("total_deposits", &self.total_deposits.to_string())
             .field("total_deposit_notes", &self.total_deposit_notes.to_string())
             .field("total_loan_notes", &self.total_loan_notes.to_string())
@@ -588,6 +1504,7 @@ mod tests {
         let calc = DepositNoteCalculator {
             outstanding_debt,
             uncollected_fees,
+            insurance_fund: Number::ZERO,
             vault_total,
             mint_supply,
         };
@@ -596,6 +1513,422 @@ mod tests {
         assert_eq!(50_000_000, notes_received);
     }
 
+    #[test]
+    fn deposit_cap_limits_total_deposits() {
+        let mut reserve = Reserve::zeroed();
+
+        reserve.deposit(900, 900);
+        assert!(reserve.verify_deposit_cap(u64::MAX).is_ok());
+
+        reserve.config.max_total_deposits = 1_000;
+        assert!(reserve.verify_deposit_cap(100).is_ok());
+        assert!(matches!(
+            reserve.verify_deposit_cap(101),
+            Err(ErrorCode::DepositCapExceeded)
+        ));
+        assert!(matches!(
+            reserve.verify_deposit_cap(u64::MAX),
+            Err(ErrorCode::DepositCapExceeded)
+        ));
+    }
+
+    #[test]
+    fn borrow_cap_limits_outstanding_debt() {
+        let mut reserve = Reserve::zeroed();
+
+        *reserve.unwrap_outstanding_debt_mut(0) = Number::from(900);
+        assert!(reserve.verify_borrow_cap(0, 1_000_000).is_ok());
+
+        reserve.config.max_outstanding_debt = 1_000;
+        assert!(reserve.verify_borrow_cap(0, 100).is_ok());
+        assert!(matches!(
+            reserve.verify_borrow_cap(0, 101),
+            Err(ErrorCode::BorrowCapExceeded)
+        ));
+    }
+
+    fn valid_config() -> ReserveConfig {
+        let mut config = ReserveConfig::zeroed();
+
+        config.utilization_rate_1 = 8500;
+        config.utilization_rate_2 = 9500;
+        config.borrow_rate_0 = 50;
+        config.borrow_rate_1 = 600;
+        config.borrow_rate_2 = 4000;
+        config.borrow_rate_3 = 16000;
+        config.min_collateral_ratio = 12500;
+        config.liquidation_premium = 300;
+        config.manage_fee_rate = 50;
+        config.loan_origination_fee = 10;
+
+        config
+    }
+
+    #[test]
+    fn validate_accepts_consistent_config() {
+        assert!(valid_config().validate().is_ok());
+
+        let mut config = valid_config();
+        config.utilization_rate_2 = 10000;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_bad_utilization_breakpoints() {
+        let mut config = valid_config();
+        config.utilization_rate_1 = config.utilization_rate_2;
+        assert!(config.validate().is_err());
+
+        let mut config = valid_config();
+        config.utilization_rate_1 = 9600;
+        assert!(config.validate().is_err());
+
+        let mut config = valid_config();
+        config.utilization_rate_1 = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = valid_config();
+        config.utilization_rate_2 = 10001;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_bad_borrow_rates() {
+        let mut config = valid_config();
+        config.borrow_rate_1 = config.borrow_rate_2 + 1;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn validate_rejects_bad_collateral_ratio() {
+        let mut config = valid_config();
+        config.min_collateral_ratio = 9000;
+        assert!(config.validate().is_err());
+
+        let mut config = valid_config();
+        config.min_collateral_ratio = 10300;
+        assert!(config.validate().is_err());
+
+        let mut config = valid_config();
+        config.liquidation_collateral_ratio = 11000;
+        assert!(config.validate().is_ok());
+
+        let mut config = valid_config();
+        config.liquidation_collateral_ratio = 13000;
+        assert!(config.validate().is_err());
+
+        let mut config = valid_config();
+        config.liquidation_collateral_ratio = 10300;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn liquidation_ratio_defaults_to_min_collateral_ratio() {
+        let mut reserve = Reserve::zeroed();
+        reserve.config = valid_config();
+
+        assert_eq!(reserve.liquidation_collateral_ratio(), 12500);
+
+        reserve.config.liquidation_collateral_ratio = 11500;
+        assert_eq!(reserve.liquidation_collateral_ratio(), 11500);
+    }
+
+    #[test]
+    fn validate_rejects_bad_fees() {
+        let mut config = valid_config();
+        config.manage_fee_rate = 10001;
+        assert!(config.validate().is_err());
+
+        let mut config = valid_config();
+        config.flash_loan_fee = 10001;
+        assert!(config.validate().is_err());
+
+        let mut config = valid_config();
+        config.collateral_factor = 10001;
+        assert!(config.validate().is_err());
+
+        let mut config = valid_config();
+        config.keeper_bounty_share = 2001;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn risk_change_limited_to_risk_parameters() {
+        let config = valid_config();
+
+        let mut new_config = config;
+        new_config.min_collateral_ratio = 15000;
+        new_config.max_total_deposits = 1_000_000;
+        assert!(config.verify_risk_change(&new_config, 10_000).is_ok());
+
+        let mut new_config = config;
+        new_config.borrow_rate_3 = 1000;
+        assert!(config.verify_risk_change(&new_config, 10_000).is_err());
+
+        let mut new_config = config;
+        new_config.manage_fee_rate = 100;
+        assert!(config.verify_risk_change(&new_config, 10_000).is_err());
+
+        let mut new_config = config;
+        new_config.keeper_bounty_share = 100;
+        assert!(config.verify_risk_change(&new_config, 10_000).is_err());
+
+        // each parameter is limited in how far it can move at once
+        let mut new_config = config;
+        new_config.min_collateral_ratio = config.min_collateral_ratio + 500;
+        assert!(config.verify_risk_change(&new_config, 500).is_ok());
+        assert!(config.verify_risk_change(&new_config, 300).is_err());
+
+        let mut config = config;
+        config.max_total_deposits = 1_000_000;
+
+        let mut new_config = config;
+        new_config.max_total_deposits = 500_000;
+        assert!(config.verify_risk_change(&new_config, 5_000).is_ok());
+        assert!(config.verify_risk_change(&new_config, 4_999).is_err());
+
+        new_config.max_total_deposits = 0;
+        assert!(config.verify_risk_change(&new_config, 10_000).is_err());
+        // defaults are compared by their effective values
+        let mut config = valid_config();
+        config.collateral_factor = 0;
+        config.liquidation_collateral_ratio = 0;
+
+        let mut new_config = config;
+        new_config.collateral_factor = 9_500;
+        new_config.liquidation_collateral_ratio = config.min_collateral_ratio - 500;
+        assert!(config.verify_risk_change(&new_config, 500).is_ok());
+
+        new_config.collateral_factor = 9_000;
+        assert!(config.verify_risk_change(&new_config, 500).is_err());
+
+        // disabled checks may be enabled, but not disabled again
+        let mut new_config = config;
+        new_config.max_oracle_age = 20;
+        new_config.oracle_max_deviation = 300;
+        new_config.price_halt_threshold = 2000;
+        new_config.price_halt_window = 10;
+        assert!(config.verify_risk_change(&new_config, 500).is_ok());
+        assert!(new_config.verify_risk_change(&config, 10_000).is_err());
+    }
+
+    #[test]
+    fn removable_only_when_deprecated_and_empty() {
+        // boxed to keep the reserve state aligned, since the reserve itself is packed
+        let mut reserve = Box::new(Reserve::zeroed());
+
+        assert!(reserve.verify_not_deprecated().is_ok());
+        assert!(matches!(
+            reserve.verify_removable(0),
+            Err(ErrorCode::ReserveNotRemovable)
+        ));
+
+        reserve.reset_flags(ReserveFlags::DEPRECATED);
+        assert!(matches!(
+            reserve.verify_not_deprecated(),
+            Err(ErrorCode::ReserveDeprecated)
+        ));
+        assert!(reserve.verify_removable(0).is_ok());
+
+        assert!(reserve.verify_removable(1).is_err());
+
+        // leftover tokens with no notes against them are swept on removal
+        let state = reserve.unwrap_state_mut(0);
+        state.total_deposits = 3;
+        state.uncollected_fees = Number::from(1);
+        state.insurance_fund = Number::from(10);
+        assert!(reserve.verify_removable(0).is_ok());
+
+        reserve.deposit(100, 100);
+        assert!(reserve.verify_removable(0).is_err());
+        reserve.withdraw(100, 100);
+
+        reserve.unwrap_state_mut(0).total_loan_notes = 1;
+        assert!(reserve.verify_removable(0).is_err());
+    }
+
+    #[test]
+    fn keeper_bounty_is_share_of_premium() {
+        let mut reserve = Box::new(Reserve::zeroed());
+        assert_eq!(0, reserve.keeper_bounty(1_000));
+
+        reserve.config.keeper_bounty_share = 2500;
+        assert_eq!(250, reserve.keeper_bounty(1_000));
+        assert_eq!(0, reserve.keeper_bounty(3));
+    }
+
+    #[test]
+    fn flash_loan_lock_rejects_reentrant_deposit() {
+        let market = Box::new(crate::state::Market::zeroed());
+        let mut reserve = Box::new(Reserve::zeroed());
+
+        reserve.begin_flash_loan(0).unwrap();
+        assert!(matches!(
+            market.verify_ability_deposit_withdraw(&reserve),
+            Err(ErrorCode::ReserveLocked)
+        ));
+        assert!(matches!(
+            reserve.begin_flash_loan(0),
+            Err(ErrorCode::ReserveLocked)
+        ));
+
+        reserve.end_flash_loan(0, 1_000, 1_010, 10).unwrap();
+        assert!(market.verify_ability_deposit_withdraw(&reserve).is_ok());
+    }
+
+    #[test]
+    fn flash_loan_requires_fresh_reserve() {
+        let mut reserve = Box::new(Reserve::zeroed());
+
+        assert!(matches!(
+            reserve.begin_flash_loan(10),
+            Err(ErrorCode::ReserveStale)
+        ));
+        assert!(reserve.verify_unlocked().is_ok());
+    }
+
+    #[test]
+    fn flash_loan_repayment_books_only_the_fee() {
+        let mut reserve = Box::new(Reserve::zeroed());
+
+        reserve.begin_flash_loan(0).unwrap();
+        assert!(matches!(
+            reserve.end_flash_loan(0, 1_000, 1_009, 10),
+            Err(ErrorCode::FlashLoanNotRepaid)
+        ));
+        assert!(reserve.verify_unlocked().is_err());
+
+        // anything returned beyond the fee isn't counted as fees
+        reserve.end_flash_loan(0, 1_000, 1_500, 10).unwrap();
+        assert!(reserve.verify_unlocked().is_ok());
+
+        let state = reserve.unwrap_state(0);
+        assert_eq!(Number::from(10), state.uncollected_fees);
+        assert_eq!(10, state.total_deposits);
+    }
+
+    #[test]
+    fn large_price_move_halts_borrows() {
+        // boxed to keep the reserve state aligned, since the reserve itself is packed
+        let mut reserve = Box::new(Reserve::zeroed());
+        reserve.config.price_halt_threshold = 2000;
+        reserve.config.price_halt_window = 10;
+
+        assert!(!reserve.record_price(100, Number::from(100)));
+        assert!(!reserve.record_price(105, Number::from(119)));
+        assert!(!reserve.flags().contains(ReserveFlags::HALT_BORROWS));
+
+        // a small move after the window has passed starts a new one
+        assert!(!reserve.record_price(200, Number::from(110)));
+        assert!(!reserve.flags().contains(ReserveFlags::HALT_BORROWS));
+
+        // a large move is still caught when it arrives after the window has passed
+        assert!(reserve.record_price(300, Number::from(50)));
+        assert!(reserve.flags().contains(ReserveFlags::HALT_BORROWS));
+
+        // and the moves within the new window are checked against it
+        reserve.reset_flags(ReserveFlags::empty());
+        assert!(reserve.record_price(301, Number::from(70)));
+        assert!(reserve.flags().contains(ReserveFlags::HALT_BORROWS));
+    }
+
+    #[test]
+    fn gradual_price_moves_within_window_halt_borrows() {
+        let mut reserve = Box::new(Reserve::zeroed());
+        reserve.config.price_halt_threshold = 2000;
+        reserve.config.price_halt_window = 10;
+
+        // each move is small, but together they exceed the threshold
+        assert!(!reserve.record_price(100, Number::from(100)));
+        assert!(!reserve.record_price(104, Number::from(112)));
+        assert!(!reserve.record_price(108, Number::from(119)));
+        assert!(reserve.record_price(110, Number::from(125)));
+    }
+
+    #[test]
+    fn pending_config_applies_after_activation() {
+        let mut reserve = Reserve::zeroed();
+
+        assert!(matches!(
+            reserve.apply_pending_config(100),
+            Err(ErrorCode::NoPendingReserveConfig)
+        ));
+
+        reserve.queue_config(valid_config(), 100);
+
+        assert!(matches!(
+            reserve.apply_pending_config(99),
+            Err(ErrorCode::PendingReserveConfigNotReady)
+        ));
+        assert_eq!({ reserve.config.min_collateral_ratio }, 0);
+
+        reserve.apply_pending_config(100).unwrap();
+
+        assert_eq!({ reserve.config.min_collateral_ratio }, 12500);
+        assert_eq!({ reserve.pending_config_activation }, 0);
+    }
+
+    #[test]
+    fn pending_config_can_be_cancelled() {
+        let mut reserve = Reserve::zeroed();
+
+        reserve.queue_config(valid_config(), 100);
+        reserve.cancel_pending_config();
+
+        assert!(matches!(
+            reserve.apply_pending_config(100),
+            Err(ErrorCode::NoPendingReserveConfig)
+        ));
+        assert_eq!({ reserve.config.min_collateral_ratio }, 0);
+    }
+
+    #[test]
+    fn pending_category_applies_after_activation() {
+        let mut reserve = Reserve::zeroed();
+
+        assert!(matches!(
+            reserve.apply_pending_category(100),
+            Err(ErrorCode::NoPendingReserveCategory)
+        ));
+
+        reserve.queue_category(3, 100);
+
+        assert!(matches!(
+            reserve.apply_pending_category(99),
+            Err(ErrorCode::PendingReserveCategoryNotReady)
+        ));
+        assert_eq!({ reserve.category }, 0);
+
+        reserve.apply_pending_category(100).unwrap();
+
+        assert_eq!({ reserve.category }, 3);
+        assert_eq!({ reserve.pending_category_activation }, 0);
+    }
+
+    #[test]
+    fn secondary_oracle_requires_deviation_limit() {
+        let mut reserve = Reserve::zeroed();
+        let mut config = valid_config();
+
+        config.oracle_max_deviation = 0;
+        reserve.verify_oracle_config(&config).unwrap();
+
+        reserve.secondary_oracle_price = Pubkey::new_unique();
+        assert!(matches!(
+            reserve.verify_oracle_config(&config),
+            Err(ErrorCode::InvalidParameter)
+        ));
+
+        reserve.queue_config(config, 100);
+        assert!(matches!(
+            reserve.apply_pending_config(100),
+            Err(ErrorCode::InvalidParameter)
+        ));
+
+        config.oracle_max_deviation = 500;
+        reserve.verify_oracle_config(&config).unwrap();
+    }
+
     #[test]
     fn sane_utilization_rate() {
         let vault_total = 100_000_000;
@@ -779,6 +2112,176 @@ mod tests {
     // In the tests below we take the interest model as correct and focus
     // the tests on the accrual mechanism and its precision.
 
+    fn curve(points: &[(u16, u16)]) -> ReserveConfig {
+        let mut config = valid_config();
+
+        config.rate_curve_len = points.len() as u8;
+        for (knot, &(utilization_rate, borrow_rate)) in config.rate_curve.iter_mut().zip(points) {
+            *knot = RateKnot {
+                utilization_rate,
+                borrow_rate,
+            };
+        }
+
+        config
+    }
+
+    #[test]
+    fn test_interest_rate_curve() {
+        let mut reserve = Reserve::zeroed();
+        reserve.config = curve(&[
+            (0, 100),
+            (8000, 500),
+            (9000, 500),
+            (9900, 1000),
+            (10000, 20000),
+        ]);
+
+        let vault_total = 1_000_000;
+        let rate = |debt: u64, vault: u64| {
+            reserve
+                .interest_rate(Number::from(debt), vault)
+                .as_u64(jet_math::BPS_EXPONENT)
+        };
+
+        // Rising to the plateau (50% utilization)
+        assert_eq!(350, rate(1_000_000, vault_total));
+
+        // On the plateau (85% utilization)
+        assert_eq!(500, rate(5_666_667, vault_total));
+
+        // On the steep final segment (99.5% utilization)
+        assert_eq!(10500, rate(199_000_000, vault_total));
+
+        // At 100% utilization
+        assert_eq!(20000, rate(1_000_000, 0));
+    }
+
+    #[test]
+    fn rate_curve_matches_regimes_when_unset() {
+        let config = valid_config();
+        let regimes = {
+            let mut reserve = Reserve::zeroed();
+            reserve.config = config;
+            reserve
+        };
+        let curve = {
+            let mut reserve = Reserve::zeroed();
+            reserve.config = curve(&[(0, 50), (8500, 600), (9500, 4000), (10000, 16000)]);
+            reserve
+        };
+
+        for debt in [0, 1_000_000, 5_666_667, 9_000_000, 19_000_000, 99_000_000] {
+            assert_eq!(
+                regimes.interest_rate(Number::from(debt), 1_000_000),
+                curve.interest_rate(Number::from(debt), 1_000_000)
+            );
+        }
+    }
+
+    #[test]
+    fn validate_rejects_bad_rate_curve() {
+        assert!(curve(&[(0, 50), (5000, 500), (10000, 2000)])
+            .validate()
+            .is_ok());
+
+        // too few points
+        assert!(curve(&[(10000, 50)]).validate().is_err());
+
+        // not covering the full utilization range
+        assert!(curve(&[(100, 50), (10000, 2000)]).validate().is_err());
+        assert!(curve(&[(0, 50), (9000, 2000)]).validate().is_err());
+
+        // utilization not increasing
+        assert!(curve(&[(0, 50), (5000, 500), (5000, 600), (10000, 2000)])
+            .validate()
+            .is_err());
+
+        // rates decreasing
+        assert!(curve(&[(0, 50), (5000, 500), (10000, 400)])
+            .validate()
+            .is_err());
+    }
+
+    fn adaptive_config() -> ReserveConfig {
+        let mut config = valid_config();
+
+        config.rate_model = 1;
+        config.adaptive_target_utilization = 8000;
+        config.adaptive_adjustment_speed = 1000;
+        config.adaptive_curve_steepness = 40000;
+        config.adaptive_min_rate = 100;
+        config.adaptive_max_rate = 5000;
+
+        config
+    }
+
+    #[test]
+    fn adaptive_rate_follows_curve_around_target() {
+        let mut reserve = Reserve::zeroed();
+        reserve.config = adaptive_config();
+
+        let rate = |debt: u64, vault: u64| {
+            reserve
+                .interest_rate(Number::from(debt), vault)
+                .as_u64(jet_math::BPS_EXPONENT)
+        };
+
+        assert_eq!(25, rate(0, 1_000_000));
+        assert_eq!(100, rate(4_000_000, 1_000_000));
+        assert_eq!(250, rate(9_000_000, 1_000_000));
+        assert_eq!(400, rate(1_000_000, 0));
+    }
+
+    #[test]
+    fn adaptive_rate_moves_with_utilization() {
+        let mut reserve = Reserve::zeroed();
+        reserve.config = adaptive_config();
+
+        *reserve.unwrap_outstanding_debt_mut(0) = Number::from(100_000_000);
+
+        // fully utilized for a day raises the rate at the target
+        reserve.try_accrue_interest(0, SECONDS_PER_DAY, 0).unwrap();
+        assert_eq!(
+            1100,
+            reserve
+                .adaptive_rate_at_target()
+                .as_u64(jet_math::BPS_EXPONENT)
+        );
+
+        // far below the target lowers it again, but not past the minimum
+        reserve
+            .try_accrue_interest(u64::MAX / 2, 7 * SECONDS_PER_DAY, 0)
+            .unwrap();
+        assert_eq!(
+            100,
+            reserve
+                .adaptive_rate_at_target()
+                .as_u64(jet_math::BPS_EXPONENT)
+        );
+    }
+
+    #[test]
+    fn validate_rejects_bad_adaptive_rate() {
+        assert!(adaptive_config().validate().is_ok());
+
+        let mut config = adaptive_config();
+        config.rate_model = 2;
+        assert!(config.validate().is_err());
+
+        let mut config = adaptive_config();
+        config.adaptive_target_utilization = 10000;
+        assert!(config.validate().is_err());
+
+        let mut config = adaptive_config();
+        config.adaptive_curve_steepness = 9000;
+        assert!(config.validate().is_err());
+
+        let mut config = adaptive_config();
+        config.adaptive_min_rate = 6000;
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn sane_interest_accrual_at_ideal_utilization_one_day() {
         let mut reserve = Reserve::zeroed();
@@ -797,7 +2300,9 @@ mod tests {
         let vault_total = 100_000_000;
         let target_time = SECONDS_PER_YEAR / (365 * 24);
 
-        reserve.try_accrue_interest(vault_total, target_time, 0);
+        reserve
+            .try_accrue_interest(vault_total, target_time, 0)
+            .unwrap();
         let owed = reserve.state().get_stale().outstanding_debt.as_u64(0);
 
         assert_eq!(100_000_342, owed);
@@ -821,7 +2326,9 @@ mod tests {
 
         let target_time = 7 * SECONDS_PER_YEAR / (365 * 24);
 
-        reserve.try_accrue_interest(vault_total, target_time, 0);
+        reserve
+            .try_accrue_interest(vault_total, target_time, 0)
+            .unwrap();
 
         let owed = reserve.state().get_stale().outstanding_debt.as_u64(0);
 
@@ -846,7 +2353,9 @@ mod tests {
 
         let target_time = SECONDS_PER_HOUR;
 
-        reserve.try_accrue_interest(vault_total, target_time, 0);
+        reserve
+            .try_accrue_interest(vault_total, target_time, 0)
+            .unwrap();
 
         let owed = reserve.state().get_stale().outstanding_debt.as_u64(0);
 
@@ -871,7 +2380,9 @@ mod tests {
 
         let target_time = 7 * SECONDS_PER_HOUR;
 
-        reserve.try_accrue_interest(vault_total, target_time, 0);
+        reserve
+            .try_accrue_interest(vault_total, target_time, 0)
+            .unwrap();
 
         let owed = reserve.state().get_stale().outstanding_debt.as_u64(0);
 
@@ -897,13 +2408,162 @@ mod tests {
 
         let target_time = 7 * SECONDS_PER_YEAR / (365 * 24);
 
-        reserve.try_accrue_interest(vault_total, target_time, 0);
+        reserve
+            .try_accrue_interest(vault_total, target_time, 0)
+            .unwrap();
 
         let owed = reserve.state().get_stale().outstanding_debt.as_u64(0);
 
         assert_eq!(owed, 100_023_975_476);
     }
 
+    #[test]
+    fn compound_interest_handles_large_rates_and_periods() {
+        // e^2 - 1, for 200% over a year
+        let growth = Reserve::compound_interest(Number::from(2), SECONDS_PER_YEAR).unwrap();
+        assert_eq!(6_389_056, growth.as_u64(-6));
+
+        // e^0.5 - 1, for 10% over five years
+        let growth =
+            Reserve::compound_interest(Number::from_bps(1000), 5 * SECONDS_PER_YEAR).unwrap();
+        assert_eq!(648_721, growth.as_u64(-6));
+
+        // e^6.5535 - 1, for the highest configurable rate over a year
+        let growth =
+            Reserve::compound_interest(Number::from_bps(u16::MAX), SECONDS_PER_YEAR).unwrap();
+        assert_eq!(700_695, growth.as_u64(-3));
+    }
+
+    #[test]
+    fn compound_interest_fails_on_overflow() {
+        assert!(matches!(
+            Reserve::compound_interest(Number::from(10), 10 * SECONDS_PER_YEAR),
+            Err(ErrorCode::ArithmeticError)
+        ));
+    }
+
+    #[test]
+    fn stale_reserve_accrues_in_one_refresh() {
+        let mut reserve = Box::new(Reserve::zeroed());
+        reserve.config = valid_config();
+
+        *reserve.unwrap_outstanding_debt_mut(0) = Number::from(100_000_000);
+
+        reserve
+            .try_accrue_interest(100_000_000, 30 * SECONDS_PER_DAY, 1)
+            .unwrap();
+
+        let state = reserve.unwrap_state(1);
+        assert_eq!(30 * SECONDS_PER_DAY, state.accrued_until);
+        assert!(state.outstanding_debt > Number::from(100_000_000));
+    }
+
+    #[test]
+    fn interest_fees_fund_insurance() {
+        let mut reserve = Box::new(Reserve::zeroed());
+        reserve.config = valid_config();
+        reserve.config.manage_fee_rate = 1000;
+        reserve.config.insurance_fee_share = 5000;
+
+        *reserve.unwrap_outstanding_debt_mut(0) = Number::from(100_000_000);
+        reserve
+            .try_accrue_interest(100_000_000, SECONDS_PER_YEAR, 1)
+            .unwrap();
+
+        let state = reserve.unwrap_state(1);
+        assert!(state.insurance_fund > Number::ZERO);
+        assert_eq!(state.insurance_fund, state.uncollected_fees);
+    }
+
+    #[test]
+    fn insurance_released_up_to_fund() {
+        let mut reserve = Box::new(Reserve::zeroed());
+
+        let state = reserve.unwrap_state_mut(0);
+        state.total_deposits = 9_000;
+        state.total_deposit_notes = 10_000;
+        state.insurance_fund = Number::from(100);
+
+        let rate_before = reserve.deposit_note_exchange_rate(0, 10_100, 10_000);
+
+        assert!(matches!(
+            reserve.release_insurance(0, 101),
+            Err(ErrorCode::InvalidParameter)
+        ));
+
+        // releasing the fund's tokens from the vault leaves depositors unaffected
+        reserve.release_insurance(0, 100).unwrap();
+        assert_eq!(Number::ZERO, reserve.unwrap_state(0).insurance_fund);
+        assert_eq!(
+            rate_before,
+            reserve.deposit_note_exchange_rate(0, 10_000, 10_000)
+        );
+    }
+
+    #[test]
+    fn written_off_debt_covered_by_insurance_first() {
+        let mut reserve = Box::new(Reserve::zeroed());
+
+        let state = reserve.unwrap_state_mut(0);
+        state.outstanding_debt = Number::from(1_000);
+        state.total_loan_notes = 1_000;
+        state.total_deposits = 9_000;
+        state.total_deposit_notes = 10_000;
+        state.insurance_fund = Number::from(100);
+
+        let rate_before = reserve.deposit_note_exchange_rate(0, 9_000, 10_000);
+
+        // fully covered by the fund
+        let (debt, covered) = reserve.write_off_debt(0, 50).unwrap();
+        assert_eq!(Number::from(50), debt);
+        assert_eq!(Number::from(50), covered);
+        assert_eq!(
+            rate_before,
+            reserve.deposit_note_exchange_rate(0, 9_000, 10_000)
+        );
+
+        // partly covered, with the rest lost by depositors
+        let (debt, covered) = reserve.write_off_debt(0, 150).unwrap();
+        assert_eq!(Number::from(150), debt);
+        assert_eq!(Number::from(50), covered);
+
+        let state = reserve.unwrap_state(0);
+        assert_eq!(Number::from(800), state.outstanding_debt);
+        assert_eq!(Number::ZERO, state.insurance_fund);
+        assert_eq!(800, state.total_loan_notes);
+
+        // can't write off more notes than were issued
+        assert!(matches!(
+            reserve.write_off_debt(0, 801),
+            Err(ErrorCode::ArithmeticError)
+        ));
+        assert_eq!(
+            Number::from(9_800) / Number::from(10_000),
+            reserve.deposit_note_exchange_rate(0, 9_000, 10_000)
+        );
+    }
+
+    #[test]
+    fn uncovered_write_offs_recorded_as_unrecovered() {
+        let mut reserve = Box::new(Reserve::zeroed());
+
+        let state = reserve.unwrap_state_mut(0);
+        state.outstanding_debt = Number::from(2_000);
+        state.total_loan_notes = 1_000;
+        state.insurance_fund = Number::from(300);
+
+        // an underwater liquidation repaid part of the loan, and left the rest
+        reserve.repay(0, 1_200, 600);
+        assert_eq!(Number::ZERO, reserve.unrecovered_debt());
+
+        // the debt stays on the reserve until the owner writes it off
+        let (debt, covered) = reserve.write_off_debt(0, 400).unwrap();
+        assert_eq!(Number::from(800), debt);
+        assert_eq!(Number::from(300), covered);
+        assert_eq!(Number::from(500), reserve.unrecovered_debt());
+        assert_eq!(Number::ZERO, reserve.unwrap_state(0).outstanding_debt);
+    }
+
     #[test]
     fn sane_interest_manage_fee_collection() {
         let mut reserve = Reserve::zeroed();
@@ -929,7 +2589,9 @@ mod tests {
 
         let target_time = 7 * SECONDS_PER_HOUR;
 
-        reserve.try_accrue_interest(vault_total, target_time, 0);
+        reserve
+            .try_accrue_interest(vault_total, target_time, 0)
+            .unwrap();
 
         let deposit_note_value = reserve.deposit_note_exchange_rate(0, vault_total, deposit_notes);
 
@@ -202,11 +202,6 @@ impl<const SIZE: usize> borsh::BorshSerialize for FixedBuf<SIZE> {
     }
 }
 
-pub enum JobCompletion {
-    Partial,
-    Full,
-}
-
 pub fn verify_account_empty(account: &AccountInfo) -> ProgramResult {
     let notes_remaining = token::accessor::amount(&account)?;
 
@@ -245,7 +245,6 @@ describe("jet-serum", () => {
         manageFeeRate: 50,
         manageFeeCollectionThreshold: new BN(10),
         loanOriginationFee: 0,
-        liquidationSlippage: 300,
         liquidationDexTradeMax: new BN(1000 * LAMPORTS_PER_SOL),
         confidenceThreshold: 500,
       },
@@ -270,7 +269,6 @@ describe("jet-serum", () => {
         manageFeeRate: 50,
         manageFeeCollectionThreshold: new BN(10),
         loanOriginationFee: 10,
-        liquidationSlippage: 300,
         liquidationDexTradeMax: new BN(1000 * LAMPORTS_PER_SOL),
         confidenceThreshold: 1000,
       },
@@ -295,7 +293,6 @@ describe("jet-serum", () => {
         manageFeeRate: 50,
         manageFeeCollectionThreshold: new BN(10),
         loanOriginationFee: 10,
-        liquidationSlippage: 300,
         liquidationDexTradeMax: new BN(1000 * LAMPORTS_PER_SOL),
         confidenceThreshold: 1000,
       },
@@ -320,7 +317,6 @@ describe("jet-serum", () => {
         manageFeeRate: 50,
         manageFeeCollectionThreshold: new BN(10),
         loanOriginationFee: 10,
-        liquidationSlippage: 300,
         liquidationDexTradeMax: new BN(1000 * LAMPORTS_PER_SOL),
         confidenceThreshold: 1500,
       },
@@ -450,7 +446,11 @@ describe("jet-serum", () => {
     assert.equal(collateralBalance.toString(), wbtc.token.amount(1).toString());
     assert.equal(loanBalance.toString(), usdc.token.amount(870).toString());
 
-    await users[3].client.liquidateDex(usdc.reserve, wbtc.reserve);
+    await users[3].client.liquidateDex(
+      usdc.reserve,
+      wbtc.reserve,
+      users[3].usdc
+    );
 
     await users[3].client.refresh();
     collateralBalance = users[3].client
@@ -492,7 +492,11 @@ describe("jet-serum", () => {
     );
     assert.equal(loanBalance.toString(), "10010000000".toString());
 
-    await users[0].client.liquidateDex(wsol.reserve, usdc.reserve);
+    await users[0].client.liquidateDex(
+      wsol.reserve,
+      usdc.reserve,
+      users[0].wsol
+    );
 
     await users[0].client.refresh();
     collateralBalance = users[0].client
@@ -509,6 +513,49 @@ describe("jet-serum", () => {
     expect(loanBalance.toNumber()).to.be.closeTo(6894191161, 10);
   });
 
+  it("allow dex liquidation routed through the quote token", async () => {
+    await utils.pyth.updatePriceAccount(weth.pythPrice, {
+      exponent: -9,
+      aggregatePriceInfo: {
+        price: 7500n * 1000000000n,
+      },
+    });
+
+    await users[1].client.refresh();
+    const findCollateral = () =>
+      users[1].client
+        .collateral()
+        .find(
+          (a) =>
+            a.mint.toBase58() == wsol.reserve.data.depositNoteMint.toBase58()
+        ).amount;
+    const findLoan = () =>
+      users[1].client
+        .loans()
+        .find(
+          (a) => a.mint.toBase58() == weth.reserve.data.loanNoteMint.toBase58()
+        ).amount;
+
+    const collateralBefore = findCollateral();
+    const loanBefore = findLoan();
+
+    // neither wsol nor weth is the quote token, so the quote reserve is needed
+    await expect(
+      users[1].client.liquidateDex(weth.reserve, wsol.reserve, users[1].weth)
+    ).to.be.rejectedWith("0x13d");
+
+    await users[1].client.liquidateDex(
+      weth.reserve,
+      wsol.reserve,
+      users[1].weth,
+      usdc.reserve
+    );
+
+    await users[1].client.refresh();
+    expect(findCollateral().lt(collateralBefore)).to.be.true;
+    expect(findLoan().lt(loanBefore)).to.be.true;
+  });
+
   it("dex liquidation with 10 collaterals", async () => {
     const MAX_POSITIONS = 10;
     const user = await createTestUser();
@@ -534,7 +581,6 @@ describe("jet-serum", () => {
             manageFeeRate: 50,
             manageFeeCollectionThreshold: new BN(10),
             loanOriginationFee: 10,
-            liquidationSlippage: 300,
             liquidationDexTradeMax: new BN(1000 * LAMPORTS_PER_SOL),
             confidenceThreshold: 1500,
           },
@@ -619,8 +665,8 @@ describe("jet-serum", () => {
     await Promise.all(assets.map((asset) => asset.reserve.sendRefreshTx()));
     await Promise.all(
       [
-        user.client.liquidateDex(usdc.reserve, assets[0].reserve),
-        user.client.liquidateDex(usdc.reserve, assets[1].reserve),
+        user.client.liquidateDex(usdc.reserve, assets[0].reserve, user.usdc),
+        user.client.liquidateDex(usdc.reserve, assets[1].reserve, user.usdc),
         assets.map((asset) => asset.reserve.sendRefreshTx()),
       ].flat()
     );
@@ -641,7 +687,8 @@ describe("jet-serum", () => {
     await expect(
       users[0].client.liquidateDex(
         wsol.reserve,
-        usdc.reserve
+        usdc.reserve,
+        users[0].wsol
       )
     ).to.be.rejectedWith("0x131");
   });
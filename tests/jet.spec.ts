@@ -5,6 +5,7 @@ import {
   JetReserve,
   JetUser,
   ReserveConfig,
+  encodeReserveConfig,
 } from "@jet-lab/jet-client";
 import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
 import {
@@ -237,7 +238,6 @@ describe("jet", async () => {
       manageFeeRate: 50,
       manageFeeCollectionThreshold: new BN(10),
       loanOriginationFee: 10,
-      liquidationSlippage: 300,
       liquidationDexTradeMax: new BN(100),
       confidenceThreshold: 200,
     } as ReserveConfig;
@@ -834,16 +834,15 @@ describe("jet", async () => {
     const newConfig = {
       utilizationRate1: 6500,
       utilizationRate2: 7500,
-      borrowRate0: 10000,
-      borrowRate1: 20000,
-      borrowRate2: 30000,
-      borrowRate3: 40000,
+      borrowRate0: 5000,
+      borrowRate1: 10000,
+      borrowRate2: 15000,
+      borrowRate3: 20000,
       minCollateralRatio: 15000,
       liquidationPremium: 120,
       manageFeeRate: 60,
       manageFeeCollectionThreshold: new BN(11),
       loanOriginationFee: 20,
-      liquidationSlippage: 350,
       liquidationDexTradeMax: new BN(120),
       confidenceThreshold: 500,
     } as ReserveConfig;
@@ -889,23 +888,22 @@ describe("jet", async () => {
     const newConfig = {
       utilizationRate1: 6500,
       utilizationRate2: 7500,
-      borrowRate0: 10000,
-      borrowRate1: 20000,
-      borrowRate2: 30000,
-      borrowRate3: 40000,
+      borrowRate0: 5000,
+      borrowRate1: 10000,
+      borrowRate2: 15000,
+      borrowRate3: 20000,
       minCollateralRatio: 15000,
       liquidationPremium: 120,
       manageFeeRate: 60,
       manageFeeCollectionThreshold: new BN(11),
       loanOriginationFee: 20,
-      liquidationSlippage: 350,
       liquidationDexTradeMax: new BN(120),
       confidenceThreshold: 100,
     } as ReserveConfig;
 
     const tx = new anchor.web3.Transaction();
     tx.add(
-      program.instruction.updateReserveConfig(newConfig, {
+      program.instruction.updateReserveConfig(encodeReserveConfig(newConfig), {
         accounts: {
           market: jetMarket.address,
           reserve: wsol.reserve.address,